@@ -0,0 +1,59 @@
+use log::info;
+use sqlx::Connection;
+
+use crate::configuration::{Config, HasherCascadeArgs};
+use crate::database::get_all_hashes;
+use crate::filter::{synthesize_negative_probes, FilterCascade, CASCADE_ALGORITHM};
+use crate::utils::Error;
+
+// How many synthetic negative probes to test level 0's false positives
+// against, floored so small databases still get a meaningfully-sized
+// negative corpus to correct against.
+const MIN_NEGATIVE_PROBES: usize = 4096;
+
+pub async fn execute(args: HasherCascadeArgs, config: &Config) -> Result<(), Error> {
+    // `verify --against-cascade`'s fast path can only quick-digest a file
+    // with `CASCADE_ALGORITHM`, so a cascade built for anything else would
+    // just sit there unused. Reject it here rather than letting someone
+    // build a cascade and discover at verify time that it never fires.
+    if args.algorithm != CASCADE_ALGORITHM {
+        return Err(Error::Config(format!(
+            "Unsupported cascade algorithm {:?}: only {} is supported (verify's fast path can't quick-digest any other algorithm yet)",
+            args.algorithm, CASCADE_ALGORITHM
+        )));
+    }
+
+    info!("Building filter cascade for algorithm {}", args.algorithm);
+
+    let mut db_conn = sqlx::SqliteConnection::connect(&config.database.db_string).await?;
+    let members = get_all_hashes(config, &args.algorithm, &mut db_conn).await?;
+
+    if members.is_empty() {
+        return Err(Error::Config(format!(
+            "No stored {} hashes to build a cascade from",
+            args.algorithm
+        )));
+    }
+
+    let digest_len = members[0].len();
+    let negatives = synthesize_negative_probes(
+        &members,
+        members.len().max(MIN_NEGATIVE_PROBES),
+        digest_len,
+        0x5eed_1234_cafe_f00d,
+    );
+
+    let cascade = FilterCascade::build(&members, &negatives, args.false_positive_rate);
+
+    info!(
+        "Cascade built with {} level(s) over {} stored {} hash(es)",
+        cascade.depth(),
+        members.len(),
+        args.algorithm
+    );
+
+    std::fs::write(&args.output, cascade.to_json(&args.algorithm))?;
+    info!("Wrote cascade to {}", args.output.display());
+
+    Ok(())
+}
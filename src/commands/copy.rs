@@ -1,17 +1,21 @@
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use log::{debug, error, info};
+use flate2::{write::GzEncoder, Compression};
+use futures::{stream, StreamExt};
+use log::{debug, error, info, warn};
 use serde_json::json;
 use sqlx::Connection;
+use tokio::sync::mpsc;
 use walkdir::WalkDir;
 
-use crate::compression::{self, CompressionAlgorithm};
+use crate::compression::{self, CompressionAlgorithm, CompressionType};
 use crate::configuration::{Config, HasherCopyArgs};
-use crate::database::insert_single_hash;
+use crate::database::{insert_chunked_hash, insert_single_hash};
 use crate::utils::Error;
-use hasher::{HashConfig, Hasher};
+use hasher::{HashConfig, Hasher, HashResult};
 
 fn output_json(file_path: &Path, file_size: usize, hashes: &[(&str, Vec<u8>)], pretty: bool) {
     let mut hash_map = serde_json::Map::new();
@@ -51,6 +55,67 @@ fn output_skip_json(path: &Path, reason: &str, pretty: bool) {
     println!("{}", output);
 }
 
+fn output_error_json(path: &Path, error: &Error, pretty: bool) {
+    let mut error_map = serde_json::Map::new();
+    error_map.insert("status".to_string(), json!("error"));
+    error_map.insert("file_path".to_string(), json!(path.display().to_string()));
+    error_map.insert("error_kind".to_string(), json!(error.kind()));
+    error_map.insert("reason".to_string(), json!(error.to_string()));
+
+    let output = if pretty {
+        serde_json::to_string_pretty(&error_map)
+    } else {
+        serde_json::to_string(&error_map)
+    }
+    .unwrap();
+
+    println!("{}", output);
+}
+
+// Cap on the human-readable reason line, so a failure with a very long
+// underlying message (e.g. a database error) doesn't blow up the log.
+const ERROR_REASON_CAP: usize = 200;
+
+// Records a non-fatal failure: a machine-readable record for JSON consumers
+// (gated the same way as `output_json`), plus a two-line human summary that
+// `--no-messages` can fully silence. The caller is still responsible for
+// counting the failure in its tally regardless of what's printed here.
+fn report_failure(path: &Path, error: &Error, args: &HasherCopyArgs) {
+    if !args.hash_options.sql_only {
+        output_error_json(path, error, args.hash_options.pretty_json);
+    }
+
+    if !args.hash_options.no_messages {
+        let reason = error.to_string();
+        let reason = if reason.chars().count() > ERROR_REASON_CAP {
+            format!("{}...", reason.chars().take(ERROR_REASON_CAP).collect::<String>())
+        } else {
+            reason
+        };
+        error!("Failed: {}", path.display());
+        error!("  [{}] {}", error.kind(), reason);
+    }
+}
+
+// Running counts of copy outcomes, reported once at the end of `execute` so
+// scripted runs over large trees can tell success from partial failure
+// without scraping logs.
+#[derive(Default, Clone)]
+struct Tally {
+    copied: u64,
+    skipped: u64,
+    failed: u64,
+}
+
+impl Tally {
+    fn report(&self) {
+        info!(
+            "Done: {} copied, {} skipped, {} failed",
+            self.copied, self.skipped, self.failed
+        );
+    }
+}
+
 async fn process_hash_results(
     path: &Path,
     file_size: usize,
@@ -75,7 +140,26 @@ async fn process_hash_results(
     Ok(())
 }
 
-fn get_file_data(path: &Path) -> Result<(bool, Vec<u8>), Error> {
+// A file's bytes tagged with whichever codec produced them. The callers in
+// this module only take the bytes (`into_bytes`); `file_existing` and
+// `get_final_dest` work out the on-disk format from the path themselves.
+enum DataBlock {
+    /// Read as-is; the source was not recognized as compressed.
+    Plain(Vec<u8>),
+    /// Decompressed from `CompressionType`-encoded source data.
+    Compressed(CompressionType, Vec<u8>),
+}
+
+impl DataBlock {
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            DataBlock::Plain(data) => data,
+            DataBlock::Compressed(_, data) => data,
+        }
+    }
+}
+
+fn get_file_data(path: &Path) -> Result<DataBlock, Error> {
     // Get initial metadata for size check and later comparison
     let initial_metadata = std::fs::metadata(path)?;
 
@@ -91,26 +175,19 @@ fn get_file_data(path: &Path) -> Result<(bool, Vec<u8>), Error> {
         return Err(Error::FileChanged);
     }
 
-    let compressor = compression::get_compressor(compression::CompressionType::Gzip, 6);
-    let is_compressed = compressor.is_compressed_path(path);
-
-    if is_compressed {
-        // Verify it's actually a gzip file by trying to decompress
-        match compression::decompress_bytes(&data, compression::CompressionType::Gzip) {
-            Ok(decompressed) => Ok((true, decompressed)),
-            Err(_) => Err(Error::Config("Invalid gzip file".to_string())),
-        }
-    } else {
-        Ok((false, data))
+    match compression::detect_compression_type(path)? {
+        Some(algorithm) => match compression::decompress_bytes(&data, algorithm) {
+            Ok(decompressed) => Ok(DataBlock::Compressed(algorithm, decompressed)),
+            Err(_) => Err(Error::Config(format!(
+                "Invalid {} file",
+                compression::get_compressor(algorithm, 6).extension()
+            ))),
+        },
+        None => Ok(DataBlock::Plain(data)),
     }
 }
 
-fn file_existing(
-    source: &Path,
-    dest: &Path,
-    args: &HasherCopyArgs,
-    _config: &Config,
-) -> Result<bool, Error> {
+fn file_existing(source: &Path, dest: &Path, args: &HasherCopyArgs) -> Result<bool, Error> {
     if !args.skip_existing {
         return Ok(false);
     }
@@ -129,30 +206,24 @@ fn file_existing(
         return Ok(false);
     }
 
-    let compressor = compression::get_compressor(
-        compression::CompressionType::Gzip,
-        args.hash_options.compression_level,
-    );
-    let source_compressed = compressor.is_compressed_path(source);
-    let dest_compressed = compressor.is_compressed_path(dest);
+    let configured_algorithm = args.hash_options.compression_algorithm;
+    let source_format = compression::detect_compression_type(source)?;
+    let dest_format = compression::detect_compression_type(dest)?;
 
     // Ensure source file exists before trying to read it
     if !source.exists() {
         return Err(Error::Config(format!("Source file does not exist: {}", source.display())));
     }
 
-    let source_data = if (source_compressed && args.hash_options.decompress) || 
-                     (source_compressed && args.hash_options.hash_uncompressed) {
+    let source_data = if let Some(format) = source_format.filter(|_| {
+        args.hash_options.decompress || args.hash_options.hash_uncompressed
+    }) {
         let compressed = std::fs::read(source)?;
-        compression::decompress_bytes(&compressed, compression::CompressionType::Gzip)?
-    } else if !source_compressed && args.hash_options.hash_compressed {
+        compression::decompress_bytes(&compressed, format)?
+    } else if source_format.is_none() && args.hash_options.hash_compressed {
         let data = std::fs::read(source)?;
-        compression::compress_bytes(
-            &data,
-            compression::CompressionType::Gzip,
-            args.hash_options.compression_level,
-        )
-        .map_err(Error::from)?
+        compression::compress_bytes(&data, configured_algorithm, args.hash_options.compression_level)
+            .map_err(Error::from)?
     } else {
         std::fs::read(source)?
     };
@@ -163,18 +234,15 @@ fn file_existing(
         return Ok(false);
     }
 
-    let dest_data = if (dest_compressed && args.hash_options.decompress) ||
-                     (dest_compressed && args.hash_options.hash_uncompressed) {
+    let dest_data = if let Some(format) = dest_format.filter(|_| {
+        args.hash_options.decompress || args.hash_options.hash_uncompressed
+    }) {
         let compressed = std::fs::read(dest)?;
-        compression::decompress_bytes(&compressed, compression::CompressionType::Gzip)?
-    } else if !dest_compressed && args.hash_options.hash_compressed {
+        compression::decompress_bytes(&compressed, format)?
+    } else if dest_format.is_none() && args.hash_options.hash_compressed {
         let data = std::fs::read(dest)?;
-        compression::compress_bytes(
-            &data,
-            compression::CompressionType::Gzip,
-            args.hash_options.compression_level,
-        )
-        .map_err(Error::from)?
+        compression::compress_bytes(&data, configured_algorithm, args.hash_options.compression_level)
+            .map_err(Error::from)?
     } else {
         std::fs::read(dest)?
     };
@@ -248,26 +316,77 @@ fn file_existing(
     Ok(false)
 }
 
+// Returns `Ok(true)` on success, `Ok(false)` if hashing failed but the error
+// was non-fatal (already reported via `report_failure`), so callers can fold
+// the outcome into their running `Tally`.
 async fn _hash_file(
     path: &Path,
     hasher: &mut Hasher,
     args: &HasherCopyArgs,
     config: &Config,
     db_conn: &mut Option<sqlx::SqliteConnection>,
-) -> Result<(), Error> {
+) -> Result<bool, Error> {
     match hasher.hash_file(path) {
         Ok((file_size, hashes)) => {
             process_hash_results(path, file_size, &hashes, args, config, db_conn).await?;
-            Ok(())
+            if args.hash_options.chunked {
+                record_chunks(path, args, config, db_conn).await?;
+            }
+            Ok(true)
         }
         Err(e) => {
+            let error = Error::from(e);
             if !args.hash_options.fail_fast {
-                error!("Failed to hash {}: {}", path.display(), e);
-                Ok(())
+                report_failure(path, &error, args);
+                Ok(false)
             } else {
-                Err(Error::from(e))
+                Err(error)
+            }
+        }
+    }
+}
+
+// Splits `path` into content-defined chunks (re-using the `hash_file_chunked`
+// engine from the regular hasher) and records the per-chunk SHA256 table
+// alongside the whole-file digest `_hash_file` already wrote, so dedup
+// decisions can later reuse already-seen blocks.
+async fn record_chunks(
+    path: &Path,
+    args: &HasherCopyArgs,
+    config: &Config,
+    db_conn: &mut Option<sqlx::SqliteConnection>,
+) -> Result<(), Error> {
+    if args.hash_options.json_only {
+        return Ok(());
+    }
+    let Some(conn) = db_conn.as_mut() else {
+        return Ok(());
+    };
+
+    let (_, chunks) = chunk_file(path, args)?;
+    crate::database::insert_chunked_hash(config, path, &chunks, conn).await
+}
+
+// The chunked-hash computation shared by the sequential and parallel copy
+// paths; only SHA256 is computed per chunk since that's the strong hash the
+// chunk table is keyed on.
+fn chunk_file(
+    path: &Path,
+    args: &HasherCopyArgs,
+) -> Result<(usize, Vec<(u64, usize, HashResult)>), Error> {
+    let mut chunk_hasher = Hasher::new(HashConfig {
+        sha256: true,
+        ..Default::default()
+    });
+    match chunk_hasher.hash_file_chunked(path) {
+        Ok(result) => Ok(result),
+        Err(e) if !args.hash_options.fail_fast => {
+            if !args.hash_options.no_messages {
+                error!("Failed to chunk {}: {}", path.display(), e);
             }
+            Ok((0, Vec::new()))
         }
+        Err(e) => Err(Error::from(e)),
     }
 }
 
@@ -277,59 +396,58 @@ async fn _hash_compressed_file(
     args: &HasherCopyArgs,
     config: &Config,
     db_conn: &mut Option<sqlx::SqliteConnection>,
-) -> Result<(), Error> {
+) -> Result<bool, Error> {
     // Hash compressed state
-    _hash_file(source, hasher, args, config, db_conn).await?;
+    let compressed_ok = _hash_file(source, hasher, args, config, db_conn).await?;
 
     // Hash decompressed state
-    if let Ok((_, data)) = get_file_data(source) {
+    let mut decompressed_ok = true;
+    if let Ok(block) = get_file_data(source) {
+        let data = block.into_bytes();
         match hasher.hash_single_buffer(&data) {
             Ok(hashes) => {
                 process_hash_results(source, data.len(), &hashes, args, config, db_conn).await?;
             }
             Err(e) => {
+                let error = Error::from(e);
                 if !args.hash_options.fail_fast {
-                    error!(
-                        "Failed to hash decompressed data for {}: {}",
-                        source.display(),
-                        e
-                    );
+                    report_failure(source, &error, args);
+                    decompressed_ok = false;
                 } else {
-                    return Err(Error::from(e));
+                    return Err(error);
                 }
             }
         }
     }
-    Ok(())
+    Ok(compressed_ok && decompressed_ok)
 }
 
 fn get_final_dest(dest: &Path, args: &HasherCopyArgs) -> PathBuf {
-    let compressor = compression::get_compressor(
-        compression::CompressionType::Gzip,
-        args.hash_options.compression_level,
-    );
-    
+    let algorithm = args.hash_options.compression_algorithm;
+    let compressor =
+        compression::get_compressor(algorithm, args.hash_options.compression_level);
+    let suffix = compressor.extension().trim_start_matches('.');
+
     if args.hash_options.compress {
-        // Don't append .gz if the file is already compressed
+        // Don't append the codec's extension if `dest` already carries it
         if !compressor.is_compressed_path(dest) {
             return dest.with_extension(format!(
-                "{}{}",
+                "{}.{}",
                 dest.extension().unwrap_or_default().to_string_lossy(),
-                compressor.extension()
+                suffix
             ));
         }
     } else if args.hash_options.decompress {
-        // Remove .gz extension if file is compressed
+        // Remove the codec's extension if the file is compressed with it
         if compressor.is_compressed_path(dest) {
-            // Get the extension without the .gz
             if let Some(ext) = dest.extension() {
                 let ext_str = ext.to_string_lossy();
-                if ext_str == "gz" {
-                    // No extension before .gz
+                if ext_str == suffix {
+                    // No extension before the codec's own (e.g. "foo.zst")
                     return dest.with_extension("");
-                } else if ext_str.ends_with(".gz") {
-                    // Has extension before .gz (e.g., .tar.gz)
-                    let base_ext = ext_str.trim_end_matches(".gz");
+                } else if ext_str.ends_with(suffix) {
+                    // Has extension before the codec's own (e.g. "foo.tar.zst")
+                    let base_ext = ext_str.trim_end_matches(suffix).trim_end_matches('.');
                     if !base_ext.is_empty() {
                         return dest.with_extension(base_ext);
                     }
@@ -341,27 +459,22 @@ fn get_final_dest(dest: &Path, args: &HasherCopyArgs) -> PathBuf {
 }
 
 fn copy_file(source: &Path, dest: &Path, args: &HasherCopyArgs) -> Result<(), Error> {
-    let compressor = compression::get_compressor(
-        compression::CompressionType::Gzip,
-        args.hash_options.compression_level,
-    );
-    let source_compressed = compressor.is_compressed_path(source);
+    let source_format = compression::detect_compression_type(source)?;
 
-    if args.hash_options.compress && !source_compressed {
+    if args.hash_options.compress && source_format.is_none() {
         // Compress uncompressed source
         let source_data = std::fs::read(source)?;
         let compressed = compression::compress_bytes(
             &source_data,
-            compression::CompressionType::Gzip,
+            args.hash_options.compression_algorithm,
             args.hash_options.compression_level,
         )
         .map_err(Error::from)?;
         std::fs::write(dest, compressed)?;
-    } else if args.hash_options.decompress && source_compressed {
+    } else if args.hash_options.decompress && source_format.is_some() {
         // Decompress compressed source
         let compressed = std::fs::read(source)?;
-        let decompressed =
-            compression::decompress_bytes(&compressed, compression::CompressionType::Gzip)?;
+        let decompressed = compression::decompress_bytes(&compressed, source_format.unwrap())?;
         std::fs::write(dest, decompressed)?;
     } else {
         // Direct copy
@@ -372,32 +485,35 @@ fn copy_file(source: &Path, dest: &Path, args: &HasherCopyArgs) -> Result<(), Er
     Ok(())
 }
 
+// Returns whether the hash(es) were recorded successfully; `false` means a
+// non-fatal failure was already reported via `report_failure`.
 async fn hash_file_based_on_options(
     source: &Path,
     final_dest: &Path,
     args: &HasherCopyArgs,
     config: &Config,
     db_conn: &mut Option<sqlx::SqliteConnection>,
-) -> Result<(), Error> {
+) -> Result<bool, Error> {
     let mut hasher = Hasher::new(HashConfig::from(&config.hashes));
-    let compressor = compression::get_compressor(compression::CompressionType::Gzip, 6);
-    let is_compressed = compressor.is_compressed_path(source);
+    let is_compressed = compression::detect_compression_type(source)?.is_some();
 
-    if args.hash_options.hash_both {
+    let ok = if args.hash_options.hash_both {
         if is_compressed {
-            _hash_compressed_file(source, &mut hasher, args, config, db_conn).await?;
+            _hash_compressed_file(source, &mut hasher, args, config, db_conn).await?
         } else {
             let path_to_hash = if args.store_source_path {
                 source
             } else {
                 final_dest
             };
-            _hash_file(path_to_hash, &mut hasher, args, config, db_conn).await?;
+            _hash_file(path_to_hash, &mut hasher, args, config, db_conn).await?
         }
     } else if args.hash_options.hash_uncompressed && is_compressed {
         // Handle compressed source when hash_uncompressed is set
         // Get the file data and decompress it
-        if let Ok((_, data)) = get_file_data(source) {
+        let mut ok = true;
+        if let Ok(block) = get_file_data(source) {
+            let data = block.into_bytes();
             match hasher.hash_single_buffer(&data) {
                 Ok(hashes) => {
                     let path_to_store = if args.store_source_path {
@@ -408,18 +524,17 @@ async fn hash_file_based_on_options(
                     process_hash_results(path_to_store, data.len(), &hashes, args, config, db_conn).await?;
                 }
                 Err(e) => {
+                    let error = Error::from(e);
                     if !args.hash_options.fail_fast {
-                        error!(
-                            "Failed to hash decompressed data for {}: {}",
-                            source.display(),
-                            e
-                        );
+                        report_failure(source, &error, args);
+                        ok = false;
                     } else {
-                        return Err(Error::from(e));
+                        return Err(error);
                     }
                 }
             }
         }
+        ok
     } else {
         let path_to_hash = if args.store_source_path {
             source
@@ -428,10 +543,122 @@ async fn hash_file_based_on_options(
         } else {
             source
         };
-        _hash_file(path_to_hash, &mut hasher, args, config, db_conn).await?;
-    }
+        _hash_file(path_to_hash, &mut hasher, args, config, db_conn).await?
+    };
 
-    Ok(())
+    Ok(ok)
+}
+
+// Synchronous counterpart to `hash_file_based_on_options`, used by the
+// parallel directory pipeline below: same branching, but it returns the
+// computed (path, size, hashes) records instead of writing them out, so it
+// can run on the blocking pool without needing a DB connection of its own.
+type ChunkList = Vec<(u64, usize, HashResult)>;
+
+// Returns the computed records plus whether hashing fully succeeded (`false`
+// means a non-fatal failure was already reported via `report_failure`), so
+// `copy_and_hash_entry` can fold the outcome into the shared `Tally`.
+fn hash_blocking(
+    source: &Path,
+    final_dest: &Path,
+    args: &HasherCopyArgs,
+    hash_config: HashConfig,
+) -> Result<(Vec<(PathBuf, usize, HashResult, Option<ChunkList>)>, bool), Error> {
+    let mut hasher = Hasher::new(hash_config);
+    hasher.set_sequential_only(args.hash_options.jobs > 1);
+    let is_compressed = compression::detect_compression_type(source)?.is_some();
+    let mut records = Vec::new();
+
+    // Chunking only applies to whole files read straight off disk (it needs
+    // a real path to re-read), not the decompressed-into-memory buffers the
+    // branches below fall back to.
+    let mut hash_one = |path: &Path, hasher: &mut Hasher| -> Result<bool, Error> {
+        match hasher.hash_file(path) {
+            Ok((file_size, hashes)) => {
+                let chunks = if args.hash_options.chunked {
+                    Some(chunk_file(path, args)?.1)
+                } else {
+                    None
+                };
+                records.push((path.to_path_buf(), file_size, hashes, chunks));
+                Ok(true)
+            }
+            Err(e) => {
+                let error = Error::from(e);
+                if !args.hash_options.fail_fast {
+                    report_failure(path, &error, args);
+                    Ok(false)
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    };
+
+    let ok = if args.hash_options.hash_both {
+        if is_compressed {
+            let mut ok = hash_one(source, &mut hasher)?;
+            if let Ok(block) = get_file_data(source) {
+                let data = block.into_bytes();
+                match hasher.hash_single_buffer(&data) {
+                    Ok(hashes) => records.push((source.to_path_buf(), data.len(), hashes, None)),
+                    Err(e) => {
+                        let error = Error::from(e);
+                        if !args.hash_options.fail_fast {
+                            report_failure(source, &error, args);
+                            ok = false;
+                        } else {
+                            return Err(error);
+                        }
+                    }
+                }
+            }
+            ok
+        } else {
+            let path_to_hash = if args.store_source_path {
+                source
+            } else {
+                final_dest
+            };
+            hash_one(path_to_hash, &mut hasher)?
+        }
+    } else if args.hash_options.hash_uncompressed && is_compressed {
+        let mut ok = true;
+        if let Ok(block) = get_file_data(source) {
+            let data = block.into_bytes();
+            match hasher.hash_single_buffer(&data) {
+                Ok(hashes) => {
+                    let path_to_store = if args.store_source_path {
+                        source
+                    } else {
+                        final_dest
+                    };
+                    records.push((path_to_store.to_path_buf(), data.len(), hashes, None));
+                }
+                Err(e) => {
+                    let error = Error::from(e);
+                    if !args.hash_options.fail_fast {
+                        report_failure(source, &error, args);
+                        ok = false;
+                    } else {
+                        return Err(error);
+                    }
+                }
+            }
+        }
+        ok
+    } else {
+        let path_to_hash = if args.store_source_path {
+            source
+        } else if args.hash_options.hash_compressed {
+            final_dest
+        } else {
+            source
+        };
+        hash_one(path_to_hash, &mut hasher)?
+    };
+
+    Ok((records, ok))
 }
 
 async fn copy_and_hash_file(
@@ -440,9 +667,9 @@ async fn copy_and_hash_file(
     args: &HasherCopyArgs,
     config: &Config,
     db_conn: &mut Option<sqlx::SqliteConnection>,
-) -> Result<(), Error> {
+) -> Result<Tally, Error> {
     if args.hash_options.dry_run {
-        return Ok(());
+        return Ok(Tally::default());
     }
 
     if let Some(parent) = dest.parent() {
@@ -451,25 +678,133 @@ async fn copy_and_hash_file(
 
     let final_dest = get_final_dest(dest, args);
 
-    if file_existing(source, &final_dest, args, config)? {
-        return Ok(());
+    if file_existing(source, &final_dest, args)? {
+        return Ok(Tally {
+            skipped: 1,
+            ..Default::default()
+        });
     }
 
     copy_file(source, &final_dest, args)?;
-    hash_file_based_on_options(source, &final_dest, args, config, db_conn).await?;
+    let ok = hash_file_based_on_options(source, &final_dest, args, config, db_conn).await?;
 
-    Ok(())
+    Ok(if ok {
+        Tally {
+            copied: 1,
+            ..Default::default()
+        }
+    } else {
+        Tally {
+            failed: 1,
+            ..Default::default()
+        }
+    })
+}
+
+// A finished hash, on its way from a worker to the single task that owns the
+// DB connection below.
+struct HashRecord {
+    path: PathBuf,
+    file_size: usize,
+    hashes: HashResult,
+    chunks: Option<ChunkList>,
+}
+
+// Outcome of the blocking copy+hash step, classified so the caller can fold
+// it into the shared `Tally` (a dry run contributes to neither copied,
+// skipped, nor failed).
+enum BlockingOutcome {
+    DryRun,
+    SkipExisting,
+    Hashed(Vec<(PathBuf, usize, HashResult, Option<ChunkList>)>, bool),
+}
+
+async fn copy_and_hash_entry(
+    base_source: Arc<PathBuf>,
+    base_dest: Arc<PathBuf>,
+    path: PathBuf,
+    args: Arc<HasherCopyArgs>,
+    hash_config: HashConfig,
+    record_tx: mpsc::Sender<HashRecord>,
+) -> Result<Tally, Error> {
+    let rel_path = path
+        .strip_prefix(base_source.as_path())
+        .map_err(|_| Error::Config("Failed to strip prefix".to_string()))?
+        .to_path_buf();
+    let dest_path = base_dest.join(&rel_path);
+
+    // The copy + hash itself is blocking CPU/IO work, so it runs on the
+    // blocking pool instead of tying up the worker that's driving it.
+    let outcome = tokio::task::spawn_blocking(move || -> Result<BlockingOutcome, Error> {
+        if args.hash_options.dry_run {
+            return Ok(BlockingOutcome::DryRun);
+        }
+
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let final_dest = get_final_dest(&dest_path, &args);
+
+        if file_existing(&path, &final_dest, &args)? {
+            return Ok(BlockingOutcome::SkipExisting);
+        }
+
+        copy_file(&path, &final_dest, &args)?;
+        let (records, ok) = hash_blocking(&path, &final_dest, &args, hash_config)?;
+        Ok(BlockingOutcome::Hashed(records, ok))
+    })
+    .await
+    .map_err(|e| Error::Join(e.to_string()))??;
+
+    let (records, ok) = match outcome {
+        BlockingOutcome::DryRun => return Ok(Tally::default()),
+        BlockingOutcome::SkipExisting => {
+            return Ok(Tally {
+                skipped: 1,
+                ..Default::default()
+            })
+        }
+        BlockingOutcome::Hashed(records, ok) => (records, ok),
+    };
+
+    for (path, file_size, hashes, chunks) in records {
+        // The receiver only closes once every worker has dropped its sender,
+        // so this can't fail before `copy_directory` itself is done waiting.
+        let _ = record_tx
+            .send(HashRecord {
+                path,
+                file_size,
+                hashes,
+                chunks,
+            })
+            .await;
+    }
+
+    Ok(if ok {
+        Tally {
+            copied: 1,
+            ..Default::default()
+        }
+    } else {
+        Tally {
+            failed: 1,
+            ..Default::default()
+        }
+    })
 }
 
 async fn copy_directory(
     base_source: &Path,
     base_dest: &Path,
-    args: &HasherCopyArgs,
+    args: Arc<HasherCopyArgs>,
     config: &Config,
     db_conn: &mut Option<sqlx::SqliteConnection>,
-) -> Result<u64, Error> {
-    let mut copied_count = 0;
+) -> Result<Tally, Error> {
+    let jobs = args.hash_options.jobs.max(1);
+    let hash_config = HashConfig::from(&config.hashes);
 
+    let mut paths = Vec::new();
     for entry in WalkDir::new(base_source)
         .min_depth(0)
         .max_depth(args.hash_options.max_depth)
@@ -478,32 +813,286 @@ async fn copy_directory(
         .sort_by_file_name()
     {
         let entry = entry?;
-        let path = entry.path();
+        if entry.path().is_file() {
+            paths.push(entry.path().to_path_buf());
+        }
+    }
 
-        if path.is_file() {
-            let rel_path = path
-                .strip_prefix(base_source)
-                .map_err(|_| Error::Config("Failed to strip prefix".to_string()))?;
-            let dest_path = base_dest.join(rel_path);
+    let (record_tx, mut record_rx) = mpsc::channel::<HashRecord>(jobs * 4);
+    let do_sql = !args.hash_options.json_only;
+    let do_json = !args.hash_options.sql_only;
 
-            if let Err(e) = copy_and_hash_file(path, &dest_path, args, config, db_conn).await {
-                let err_msg = format!("Failed to copy {}: {}", path.display(), e);
-                if !args.hash_options.fail_fast {
-                    error!("{}", err_msg);
-                    continue;
+    // This is the only place that ever touches `db_conn`; workers hand off
+    // finished hashes over the channel instead of writing to SQLite
+    // themselves, so the connection never sees concurrent access.
+    let writer_args = args.clone();
+    let db_writer = async move {
+        let mut db_error = None;
+        while let Some(record) = record_rx.recv().await {
+            if do_json {
+                output_json(
+                    &record.path,
+                    record.file_size,
+                    &record.hashes,
+                    writer_args.hash_options.pretty_json,
+                );
+            }
+            if do_sql {
+                if let Some(conn) = db_conn.as_mut() {
+                    if let Err(e) =
+                        insert_single_hash(config, &record.path, record.file_size, &record.hashes, conn).await
+                    {
+                        db_error.get_or_insert(e);
+                    }
+                    if let Some(chunks) = &record.chunks {
+                        if let Err(e) = insert_chunked_hash(config, &record.path, chunks, conn).await {
+                            db_error.get_or_insert(e);
+                        }
+                    }
                 }
-                return Err(e);
             }
-            copied_count += 1;
         }
+        db_error
+    };
+
+    let base_source = Arc::new(base_source.to_path_buf());
+    let base_dest = Arc::new(base_dest.to_path_buf());
+    let worker_error: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
+    let tally = Arc::new(Mutex::new(Tally::default()));
+
+    let workers = stream::iter(paths.into_iter().map(move |path| {
+        let base_source = base_source.clone();
+        let base_dest = base_dest.clone();
+        let args = args.clone();
+        let record_tx = record_tx.clone();
+        let worker_error = worker_error.clone();
+        let tally = tally.clone();
+        let hash_config = hash_config.clone();
+        async move {
+            // Once a fail-fast error is recorded, stop starting new work but
+            // let whatever's already in flight finish.
+            if worker_error.lock().unwrap().is_some() {
+                return;
+            }
+
+            let path_for_report = path.clone();
+            match copy_and_hash_entry(base_source, base_dest, path, args.clone(), hash_config, record_tx).await {
+                Ok(outcome) => {
+                    let mut tally = tally.lock().unwrap();
+                    tally.copied += outcome.copied;
+                    tally.skipped += outcome.skipped;
+                    tally.failed += outcome.failed;
+                }
+                Err(e) => {
+                    if args.hash_options.fail_fast {
+                        worker_error.lock().unwrap().get_or_insert(e);
+                    } else {
+                        report_failure(&path_for_report, &e, &args);
+                        tally.lock().unwrap().failed += 1;
+                    }
+                }
+            }
+        }
+    }))
+    .buffer_unordered(jobs)
+    .collect::<Vec<()>>();
+
+    let (_, db_error) = futures::join!(workers, db_writer);
+
+    if let Some(e) = worker_error.lock().unwrap().take() {
+        return Err(e);
+    }
+    if let Some(e) = db_error {
+        return Err(e);
+    }
+
+    Ok(tally.lock().unwrap().clone())
+}
+
+// Wraps an archive's output stream so the caller can build a `tar::Builder`
+// on top of it without caring whether the bytes are going straight to disk or
+// through a compressor, while still being able to `finish()` the codec
+// (flate2/zstd encoders don't implement this through `Write` alone).
+enum ArchiveWriter {
+    Plain(File),
+    Gzip(GzEncoder<File>),
+    Zstd(zstd::Encoder<'static, File>),
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            ArchiveWriter::Plain(w) => w.write(buf),
+            ArchiveWriter::Gzip(w) => w.write(buf),
+            ArchiveWriter::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ArchiveWriter::Plain(w) => w.flush(),
+            ArchiveWriter::Gzip(w) => w.flush(),
+            ArchiveWriter::Zstd(w) => w.flush(),
+        }
+    }
+}
+
+impl ArchiveWriter {
+    fn new(out_file: File, algorithm: Option<CompressionType>, level: u32) -> Result<Self, Error> {
+        Ok(match algorithm {
+            Some(CompressionType::Gzip) => {
+                ArchiveWriter::Gzip(GzEncoder::new(out_file, Compression::new(level)))
+            }
+            Some(CompressionType::Zstd) => {
+                ArchiveWriter::Zstd(zstd::Encoder::new(out_file, (level as i32).clamp(1, 22))?)
+            }
+            Some(other) => {
+                warn!(
+                    "Archive mode only supports gzip/zstd compression, writing an uncompressed tar instead of {:?}",
+                    other
+                );
+                ArchiveWriter::Plain(out_file)
+            }
+            None => ArchiveWriter::Plain(out_file),
+        })
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        match self {
+            ArchiveWriter::Plain(mut w) => w.flush()?,
+            ArchiveWriter::Gzip(w) => {
+                w.finish()?;
+            }
+            ArchiveWriter::Zstd(w) => {
+                w.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Tees bytes read from `inner` into `hasher` as they're consumed, so the tar
+// builder and the digest are computed from the same single pass over the
+// file instead of reading it twice.
+struct HashingReader<'a, R> {
+    inner: R,
+    hasher: &'a mut Hasher,
+}
+
+impl<'a, R: Read> Read for HashingReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.hasher
+                .update(&buf[..n])
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        }
+        Ok(n)
+    }
+}
+
+async fn copy_directory_as_archive(
+    base_source: &Path,
+    archive_dest: &Path,
+    args: &HasherCopyArgs,
+    config: &Config,
+    db_conn: &mut Option<sqlx::SqliteConnection>,
+) -> Result<Tally, Error> {
+    if let Some(parent) = archive_dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let codec = args.hash_options.compress.then_some(args.hash_options.compression_algorithm);
+    let writer = ArchiveWriter::new(
+        File::create(archive_dest)?,
+        codec,
+        args.hash_options.compression_level,
+    )?;
+    let mut tar_builder = tar::Builder::new(writer);
+
+    let mut tally = Tally::default();
+
+    for entry in WalkDir::new(base_source)
+        .min_depth(1)
+        .max_depth(args.hash_options.max_depth)
+        .follow_links(!args.hash_options.no_follow_symlinks)
+        .sort_by_file_name()
+    {
+        let entry = entry?;
+        let path = entry.path();
+
+        if !path.is_file() {
+            continue;
+        }
+
+        let rel_path = path
+            .strip_prefix(base_source)
+            .map_err(|_| Error::Config("Failed to strip prefix".to_string()))?;
+
+        if let Err(e) = add_file_to_archive(
+            &mut tar_builder,
+            path,
+            rel_path,
+            args,
+            config,
+            db_conn,
+        )
+        .await
+        {
+            if !args.hash_options.fail_fast {
+                report_failure(path, &e, args);
+                tally.failed += 1;
+                continue;
+            }
+            return Err(e);
+        }
+        tally.copied += 1;
     }
 
-    Ok(copied_count)
+    let writer = tar_builder.into_inner()?;
+    writer.finish()?;
+
+    Ok(tally)
+}
+
+async fn add_file_to_archive(
+    tar_builder: &mut tar::Builder<ArchiveWriter>,
+    path: &Path,
+    rel_path: &Path,
+    args: &HasherCopyArgs,
+    config: &Config,
+    db_conn: &mut Option<sqlx::SqliteConnection>,
+) -> Result<(), Error> {
+    let metadata = std::fs::metadata(path)?;
+    let file_size = metadata.len();
+    let mtime = metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut hasher = Hasher::new(HashConfig::from(&config.hashes));
+    let source_file = File::open(path)?;
+    let mut reader = HashingReader {
+        inner: BufReader::new(source_file),
+        hasher: &mut hasher,
+    };
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(file_size);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+
+    tar_builder.append_data(&mut header, rel_path, &mut reader)?;
+
+    let hashes = hasher.finalize()?;
+    process_hash_results(rel_path, file_size as usize, &hashes, args, config, db_conn).await
 }
 
 pub async fn execute(args: HasherCopyArgs, config: &Config) -> Result<(), Error> {
-    let source = &args.source;
-    let dest = &args.destination;
+    let source = args.source.clone();
+    let dest = args.destination.clone();
 
     if !source.exists() {
         return Err(Error::Config("Source path does not exist".to_string()));
@@ -515,30 +1104,38 @@ pub async fn execute(args: HasherCopyArgs, config: &Config) -> Result<(), Error>
         None
     };
 
-    let copied_count = if source.is_file() {
+    // Shared so the parallel directory pipeline below can hand a cheap clone
+    // to each worker instead of copying the whole args struct.
+    let args = Arc::new(args);
+
+    let tally = if source.is_file() {
         let dest_path = if dest.is_dir() {
             dest.join(source.file_name().unwrap())
         } else {
             dest.to_path_buf()
         };
 
-        copy_and_hash_file(source, &dest_path, &args, config, &mut db_conn).await?;
-        1
+        copy_and_hash_file(&source, &dest_path, &args, config, &mut db_conn).await?
     } else {
         // Get absolute paths without the \\?\ prefix on Windows
         let base_source = if source.is_absolute() {
-            source.to_path_buf()
+            source.clone()
         } else {
-            std::env::current_dir()?.join(source)
+            std::env::current_dir()?.join(&source)
         };
         let base_dest = if dest.is_absolute() {
-            dest.to_path_buf()
+            dest.clone()
         } else {
-            std::env::current_dir()?.join(dest)
+            std::env::current_dir()?.join(&dest)
         };
-        copy_directory(&base_source, &base_dest, &args, config, &mut db_conn).await?
+
+        if args.archive {
+            copy_directory_as_archive(&base_source, &base_dest, &args, config, &mut db_conn).await?
+        } else {
+            copy_directory(&base_source, &base_dest, args.clone(), config, &mut db_conn).await?
+        }
     };
 
-    info!("Successfully copied {} files", copied_count);
+    tally.report();
     Ok(())
 }
@@ -64,14 +64,37 @@ async fn read_url_list(path: &Path) -> Result<Vec<String>, Error> {
     Ok(urls)
 }
 
-fn build_download_config(args: &HasherDownloadArgs) -> DownloadConfig {
-    DownloadConfig {
+// Parse a `--expected-hash` value of the form `<algorithm>:<hex digest>`.
+fn parse_expected_hash(spec: &str) -> Result<(String, Vec<u8>), Error> {
+    let (algorithm, hex_digest) = spec.split_once(':').ok_or_else(|| {
+        Error::Download(format!(
+            "Invalid --expected-hash {:?}, expected ALGORITHM:HEX",
+            spec
+        ))
+    })?;
+    let digest = hex::decode(hex_digest)
+        .map_err(|e| Error::Download(format!("Invalid hex digest in --expected-hash: {}", e)))?;
+    Ok((algorithm.to_string(), digest))
+}
+
+fn build_download_config(args: &HasherDownloadArgs) -> Result<DownloadConfig, Error> {
+    let expected_hash = args
+        .expected_hash
+        .as_deref()
+        .map(parse_expected_hash)
+        .transpose()?;
+
+    Ok(DownloadConfig {
         retry_count: args.hash_options.retry_count,
         retry_delay: std::time::Duration::from_secs(args.hash_options.retry_delay as u64),
         compress: args.hash_options.compress,
         compression_level: args.hash_options.compression_level,
         no_clobber: args.no_clobber,
-    }
+        expected_hash,
+        max_concurrent: args.max_concurrent,
+        max_bytes_per_sec: args.max_bytes_per_sec,
+        disk_budget: args.disk_budget,
+    })
 }
 
 fn build_result_json(result: &DownloadResult, pretty: bool) -> String {
@@ -121,7 +144,7 @@ async fn process_download_result(
             &result.path,
             config,
             &args.hash_options,
-            &mut None,
+            &mut crate::output::HashWriter::None,
         )
         .await
         {
@@ -186,7 +209,7 @@ pub async fn execute(args: HasherDownloadArgs, config: &Config) -> Result<(), Er
 
     let should_compress = args.hash_options.compress;
     let compression_level = args.hash_options.compression_level;
-    let downloader = Downloader::new(build_download_config(&args));
+    let downloader = Downloader::new(build_download_config(&args)?);
     let mut stream = downloader
         .download_from_list(urls, &args.destination, move |url| {
             let base_path = construct_download_path(url, Path::new(""))
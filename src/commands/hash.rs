@@ -27,6 +27,6 @@ pub async fn execute(args: HasherHashArgs, config: &Config) -> Result<Option<ser
         )
         .await
     } else {
-        output::process_directory(&input_path, &args.hash_options, &config).await
+        output::process_directory(&input_path, &args.hash_options, &config, args.resume).await
     }
 }
@@ -0,0 +1,5 @@
+pub mod cascade;
+pub mod copy;
+pub mod download;
+pub mod hash;
+pub mod verify;
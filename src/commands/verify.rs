@@ -1,94 +1,213 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use futures::{stream, StreamExt};
 use log::{error, info, warn};
 use serde_json::Value;
 use sqlx::Connection;
 
-use crate::compression::{self, CompressionAlgorithm};
+use crate::compression::{self, CompressionAlgorithm, CompressionType};
 use crate::configuration::{Config, HasherVerifyArgs};
-use crate::database::{get_all_paths, get_file_hashes};
+use crate::database::{get_all_paths, get_file_chunks, get_file_hashes};
+use crate::filter::{FilterCascade, CASCADE_ALGORITHM};
 use crate::utils::Error;
 use hasher::{HashConfig, Hasher};
 
-fn extract_stored_hashes(
-    stored_hashes: &[(String, (usize, Vec<u8>))],
-) -> (bool, bool, Vec<u8>, Vec<u8>, usize) {
-    let stored_size = stored_hashes
-        .first()
-        .map(|(_, (size, _))| *size)
-        .unwrap_or_default();
-    let mut found_crc32 = false;
-    let mut found_sha256 = false;
-    let mut stored_crc32 = Vec::new();
-    let mut stored_sha256 = Vec::new();
-
-    for (name, (_, hash)) in stored_hashes {
-        match name.as_str() {
-            "crc32" => {
-                found_crc32 = true;
-                stored_crc32 = hash.clone();
-            }
-            "sha256" => {
-                found_sha256 = true;
-                stored_sha256 = hash.clone();
-            }
-            _ => {}
-        }
+// A cheap single-algorithm digest of `path`'s current, literal on-disk bytes,
+// used only to probe a filter cascade before paying for the full
+// verification pipeline. Returns `None` when the cascade wasn't built from
+// `CASCADE_ALGORITHM` (the only one this can compute directly), or when
+// `path`'s stored hash wasn't computed over its literal on-disk bytes in the
+// first place - a compressed file, or one that's forced through the
+// compressed-hashing path via `--hash-compressed` - in which case falling
+// through to the codec-aware comparison below is the only way to get the
+// right answer.
+fn quick_digest(path: &Path, algorithm: &str, args: &HasherVerifyArgs) -> Result<Option<(usize, Vec<u8>)>, Error> {
+    if algorithm != CASCADE_ALGORITHM {
+        return Ok(None);
+    }
+    if args.hash_options.hash_compressed || compression::detect_compression_type(path)?.is_some() {
+        return Ok(None);
     }
 
-    (
-        found_crc32,
-        found_sha256,
-        stored_crc32,
-        stored_sha256,
-        stored_size,
-    )
+    let mut hasher = Hasher::new(HashConfig {
+        sha256: true,
+        ..Default::default()
+    });
+    let (size, hashes) = hasher.hash_file(path)?;
+    Ok(hashes
+        .into_iter()
+        .find(|(name, _)| *name == "sha256")
+        .map(|(_, digest)| (size, digest)))
 }
 
-fn extract_current_hashes(current_hashes: &[(&str, Vec<u8>)]) -> (Vec<u8>, Vec<u8>) {
-    let mut current_crc32 = Vec::new();
-    let mut current_sha256 = Vec::new();
+// Splits `path` into content-defined chunks and hashes each one with SHA256
+// only, the strong hash the chunk tables are keyed on (mirrors `chunk_file`
+// in output.rs/commands/copy.rs, which do the same thing for the hashing and
+// copy-and-hash paths respectively).
+fn chunk_current_file(path: &Path) -> Result<Vec<(u64, usize, Vec<u8>)>, Error> {
+    let mut chunk_hasher = Hasher::new(HashConfig {
+        sha256: true,
+        ..Default::default()
+    });
+    let (_, chunks) = chunk_hasher.hash_file_chunked(path)?;
+    chunks
+        .into_iter()
+        .map(|(offset, length, hashes)| {
+            hashes
+                .into_iter()
+                .find(|(name, _)| *name == "sha256")
+                .map(|(_, hash)| (offset, length, hash))
+                .ok_or_else(|| Error::Config("Chunked verification requires sha256".to_string()))
+        })
+        .collect()
+}
 
-    for (name, hash) in current_hashes {
-        match *name {
-            "crc32" => current_crc32 = hash.clone(),
-            "sha256" => current_sha256 = hash.clone(),
-            _ => {}
+// Compares stored vs. freshly computed chunk lists index-by-index by hash
+// (content-defined chunking re-syncs after a localized edit, so chunks after
+// it keep their hashes even when their offsets shift, as long as the edit
+// doesn't change how many chunks precede them) and reports the byte range of
+// every chunk whose hash no longer matches. Chunks only present in one list (the file grew or shrank a whole
+// chunk's worth) are reported as changed too.
+fn changed_chunk_ranges(stored: &[(u64, usize, Vec<u8>)], current: &[(u64, usize, Vec<u8>)]) -> Vec<(u64, usize)> {
+    let common = stored.len().min(current.len());
+    let mut ranges = Vec::new();
+
+    for (stored_chunk, current_chunk) in stored[..common].iter().zip(&current[..common]) {
+        if stored_chunk.2 != current_chunk.2 {
+            let (offset, length, _) = current_chunk;
+            ranges.push((*offset, *length));
         }
     }
 
-    (current_crc32, current_sha256)
+    for (offset, length, _) in stored[common..].iter().chain(&current[common..]) {
+        ranges.push((*offset, *length));
+    }
+
+    ranges
 }
 
-fn validate_hashes(
-    found_crc32: bool,
-    found_sha256: bool,
-    current_crc32: &[u8],
-    current_sha256: &[u8],
-    stored_crc32: &[u8],
-    stored_sha256: &[u8],
-) -> Option<(String, Vec<u8>, Vec<u8>)> {
-    if !found_crc32 || !found_sha256 {
-        return None;
+// Recomputes chunk boundaries for `path` and diffs them against what's
+// stored, when `path` was hashed with `--chunked`; returns an empty list
+// (nothing to report) when it wasn't, so callers can fall back to the plain
+// whole-file mismatch report.
+async fn compute_changed_ranges(
+    config: &Config,
+    path: &Path,
+    db_conn: &mut sqlx::SqliteConnection,
+) -> Result<Vec<(u64, usize)>, Error> {
+    let stored_chunks = get_file_chunks(config, path, db_conn).await?;
+    if stored_chunks.is_empty() {
+        return Ok(Vec::new());
     }
 
-    if current_crc32 != stored_crc32 {
-        return Some((
-            "crc32".to_string(),
-            current_crc32.to_vec(),
-            stored_crc32.to_vec(),
-        ));
-    }
+    let current_chunks = chunk_current_file(path)?;
+    Ok(changed_chunk_ranges(&stored_chunks, &current_chunks))
+}
+
+// Every codec `compressed_candidates` will probe for, in the same order
+// `compression::detect_compression_type_from_extension` checks them.
+const PROBE_ALGORITHMS: &[CompressionType] = &[
+    CompressionType::Gzip,
+    CompressionType::Zstd,
+    CompressionType::Lz4,
+    CompressionType::Brotli,
+    CompressionType::Xz,
+    CompressionType::Bzip2,
+];
+
+// Candidate paths for a file recorded uncompressed that may since have been
+// stored compressed under any supported codec, e.g. `foo.txt` -> `foo.txt.zst`.
+fn compressed_candidates(path: &Path) -> Vec<(PathBuf, CompressionType)> {
+    let orig_ext = path.extension().unwrap_or_default().to_string_lossy().to_string();
+    PROBE_ALGORITHMS
+        .iter()
+        .map(|algorithm| {
+            let suffix = compression::get_compressor(*algorithm, 1)
+                .extension()
+                .trim_start_matches('.')
+                .to_string();
+            (path.with_extension(format!("{}.{}", orig_ext, suffix)), *algorithm)
+        })
+        .collect()
+}
+
+// One named digest: an algorithm name paired with its bytes. Replaces the
+// crc32/sha256-only tuples this module used to assume were the only two
+// hashes worth comparing, so verification works against whatever algorithms
+// a database was actually built with (blake3, sha512, ...).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hash {
+    algorithm: String,
+    digest: Vec<u8>,
+}
+
+fn extract_stored_hashes(stored_hashes: &[(String, (usize, Vec<u8>))]) -> (Vec<Hash>, usize) {
+    let stored_size = stored_hashes
+        .first()
+        .map(|(_, (size, _))| *size)
+        .unwrap_or_default();
+
+    let hashes = stored_hashes
+        .iter()
+        .map(|(name, (_, digest))| Hash {
+            algorithm: name.clone(),
+            digest: digest.clone(),
+        })
+        .collect();
+
+    (hashes, stored_size)
+}
+
+fn extract_current_hashes(current_hashes: &[(&str, Vec<u8>)]) -> Vec<Hash> {
+    current_hashes
+        .iter()
+        .map(|(name, digest)| Hash {
+            algorithm: name.to_string(),
+            digest: digest.clone(),
+        })
+        .collect()
+}
+
+// What comparing a file's freshly computed hashes against its stored row
+// found: every algorithm in common matched, one of them didn't, or the two
+// sets didn't share any algorithm at all (e.g. the row was written with
+// blake3 but this run only computed sha256).
+enum HashComparison {
+    Match,
+    Mismatch {
+        algorithm: String,
+        current: Vec<u8>,
+        stored: Vec<u8>,
+    },
+    NoCommonAlgorithm,
+}
 
-    if current_sha256 != stored_sha256 {
-        return Some((
-            "sha256".to_string(),
-            current_sha256.to_vec(),
-            stored_sha256.to_vec(),
-        ));
+// Compares the intersection of algorithms present in both `current` and
+// `stored`, rather than demanding crc32+sha256 specifically.
+fn validate_hashes(current: &[Hash], stored: &[Hash]) -> HashComparison {
+    let mut compared_any = false;
+
+    for stored_hash in stored {
+        let Some(current_hash) = current.iter().find(|h| h.algorithm == stored_hash.algorithm) else {
+            continue;
+        };
+
+        compared_any = true;
+        if current_hash.digest != stored_hash.digest {
+            return HashComparison::Mismatch {
+                algorithm: stored_hash.algorithm.clone(),
+                current: current_hash.digest.clone(),
+                stored: stored_hash.digest.clone(),
+            };
+        }
     }
 
-    None
+    if compared_any {
+        HashComparison::Match
+    } else {
+        HashComparison::NoCommonAlgorithm
+    }
 }
 
 fn build_verification_json(
@@ -97,6 +216,7 @@ fn build_verification_json(
     stored_size: usize,
     failed_hash: Option<(String, Vec<u8>, Vec<u8>)>,
     is_missing: bool,
+    changed_ranges: &[(u64, usize)],
 ) -> String {
     let is_valid = failed_hash.is_none() && !is_missing;
     let path_str = path.display().to_string();
@@ -129,8 +249,18 @@ fn build_verification_json(
         (String::new(), String::new())
     };
 
+    let ranges_part = if changed_ranges.is_empty() {
+        String::new()
+    } else {
+        let items: Vec<String> = changed_ranges
+            .iter()
+            .map(|(offset, length)| format!(r#"{{"offset":{},"length":{}}}"#, offset, length))
+            .collect();
+        format!(r#","changed_ranges":[{}]"#, items.join(","))
+    };
+
     format!(
-        r#"{{"valid":{},"original":{{"path":"{}","size":{},"hash":"{}"}}{}{}}}"#,
+        r#"{{"valid":{},"original":{{"path":"{}","size":{},"hash":"{}"}}{}{}{}}}"#,
         is_valid,
         path_str,
         stored_size,
@@ -140,36 +270,38 @@ fn build_verification_json(
         } else {
             String::new()
         },
-        algorithm_part
+        algorithm_part,
+        ranges_part
     )
 }
 
+// What verifying one path against the database produced: either the
+// freshly computed hash set to compare against the stored one, or a
+// confirmation that a zstd frame's own content checksum already proved the
+// decompressed content intact, letting the caller skip that comparison (and
+// the hashing pass that would've fed it) entirely.
+enum CompressedVerification {
+    Hashed { size: Option<usize>, hashes: Vec<Hash> },
+    ChecksumVerified { size: usize },
+}
+
 async fn _hash_compressed_file(
     file_path: &Path,
+    algorithm: CompressionType,
+    config: &Config,
     args: &HasherVerifyArgs,
-) -> Result<(Option<usize>, (Vec<u8>, Vec<u8>)), Error> {
-    let mut hasher = Hasher::new(HashConfig {
-        crc32: true,
-        sha256: true,
-        ..Default::default()
-    });
+) -> Result<CompressedVerification, Error> {
+    let mut hasher = Hasher::new(HashConfig::from(&config.hashes));
 
-    let compressor = compression::get_compressor(
-        compression::CompressionType::Gzip,
-        args.hash_options.compression_level,
-    );
+    let compressor = compression::get_compressor(algorithm, args.hash_options.compression_level);
     let compressed_data = if compressor.is_compressed_path(file_path) {
         tokio::fs::read(file_path).await?
     } else if args.hash_options.hash_compressed {
         let data = tokio::fs::read(file_path).await?;
-        compression::compress_bytes(
-            &data,
-            compression::CompressionType::Gzip,
-            args.hash_options.compression_level,
-        )
-        .map_err(Error::from)?
+        compression::compress_bytes(&data, algorithm, args.hash_options.compression_level)
+            .map_err(Error::from)?
     } else {
-        return _hash_file(file_path).await;
+        return _hash_file(file_path, config).await;
     };
 
     if args.hash_options.hash_both {
@@ -177,173 +309,363 @@ async fn _hash_compressed_file(
         let comp_hashes = hasher.hash_single_buffer(&compressed_data)?;
         let comp_result = extract_current_hashes(&comp_hashes);
 
-        let decompressed =
-            compression::decompress_bytes(&compressed_data, compression::CompressionType::Gzip)?;
+        let decompressed = compression::decompress_bytes(&compressed_data, algorithm)?;
         let decomp_hashes = hasher.hash_single_buffer(&decompressed)?;
         let decomp_result = extract_current_hashes(&decomp_hashes);
 
         // Return the compressed result if hash_compressed is true, otherwise return decompressed
-        if args.hash_options.hash_compressed {
-            Ok((Some(compressed_data.len()), comp_result))
+        let (size, hashes) = if args.hash_options.hash_compressed {
+            (Some(compressed_data.len()), comp_result)
         } else {
-            Ok((Some(decompressed.len()), decomp_result))
-        }
+            (Some(decompressed.len()), decomp_result)
+        };
+        Ok(CompressedVerification::Hashed { size, hashes })
     } else if args.hash_options.decompress || args.hash_options.hash_uncompressed {
-        // Only hash decompressed state - applies for both decompress and hash_uncompressed
-        let decompressed =
-            compression::decompress_bytes(&compressed_data, compression::CompressionType::Gzip)?;
+        // Only hash decompressed state - applies for both decompress and hash_uncompressed.
+        // When the caller trusts the codec's own integrity check, try the
+        // zstd frame's content checksum first; a full rehash only happens
+        // when the fast path isn't available (not zstd, or no checksum flag).
+        if algorithm == CompressionType::Zstd && args.hash_options.trust_codec_integrity {
+            match compression::verify_zstd_frame_checksum(&compressed_data).map_err(Error::from)? {
+                Some(decompressed_size) => {
+                    return Ok(CompressedVerification::ChecksumVerified {
+                        size: decompressed_size,
+                    })
+                }
+                None => info!(
+                    "{}: zstd frame carries no content checksum; falling back to full rehash",
+                    file_path.display()
+                ),
+            }
+        }
+
+        let decompressed = compression::decompress_bytes(&compressed_data, algorithm)?;
         let hashes = hasher.hash_single_buffer(&decompressed)?;
-        Ok((Some(decompressed.len()), extract_current_hashes(&hashes)))
+        Ok(CompressedVerification::Hashed {
+            size: Some(decompressed.len()),
+            hashes: extract_current_hashes(&hashes),
+        })
     } else {
         // Only hash compressed state
         let hashes = hasher.hash_single_buffer(&compressed_data)?;
-        Ok((Some(compressed_data.len()), extract_current_hashes(&hashes)))
+        Ok(CompressedVerification::Hashed {
+            size: Some(compressed_data.len()),
+            hashes: extract_current_hashes(&hashes),
+        })
     }
 }
 
-async fn _hash_file(path: &Path) -> Result<(Option<usize>, (Vec<u8>, Vec<u8>)), Error> {
-    let mut hasher = Hasher::new(HashConfig {
-        crc32: true,
-        sha256: true,
-        ..Default::default()
-    });
+async fn _hash_file(path: &Path, config: &Config) -> Result<CompressedVerification, Error> {
+    let mut hasher = Hasher::new(HashConfig::from(&config.hashes));
     info!("Verifying {}", path.display());
     let (size, hashes) = hasher.hash_file(path)?;
-    Ok((Some(size), extract_current_hashes(&hashes)))
+    Ok(CompressedVerification::Hashed {
+        size: Some(size),
+        hashes: extract_current_hashes(&hashes),
+    })
+}
+
+// Everything `execute` needs to tally and report one verified path, computed
+// by a single `verify_file` call, so `execute` derives every counter from it
+// instead of verifying the path a second time just to recompute
+// `mismatch_count`.
+struct VerificationOutcome {
+    path: PathBuf,
+    missing: bool,
+    mismatch: bool,
+    failed_algorithm: Option<String>,
+    current_size: Option<usize>,
+    stored_size: usize,
+    current_hash: Vec<u8>,
+    stored_hash: Vec<u8>,
+    changed_ranges: Vec<(u64, usize)>,
+    // Set for rows that were never reportable at all (no stored hashes, or no
+    // algorithm in common with what was just computed) rather than merely
+    // valid; `mismatches_only` only suppresses valid reports, not these.
+    skip_report: bool,
+}
+
+impl VerificationOutcome {
+    fn valid(&self) -> bool {
+        !self.missing && !self.mismatch
+    }
 }
 
 async fn verify_file(
     path: &Path,
     args: &HasherVerifyArgs,
-    db_conn: &mut sqlx::SqliteConnection,
-) -> Result<(), Error> {
-    let stored_hashes = get_file_hashes(path, db_conn).await?;
-    let (found_crc32, found_sha256, stored_crc32, stored_sha256, stored_size) =
-        extract_stored_hashes(&stored_hashes);
-
-    if !found_crc32 || !found_sha256 {
-        warn!("Missing required hashes for {}", path.display());
-        return Ok(());
+    config: &Config,
+    cascade: Option<&(String, FilterCascade)>,
+) -> Result<VerificationOutcome, Error> {
+    let mut db_conn = sqlx::SqliteConnection::connect(&config.database.db_string).await?;
+
+    let stored_hashes = get_file_hashes(path, &mut db_conn).await?;
+    let (stored_hashes, stored_size) = extract_stored_hashes(&stored_hashes);
+
+    if stored_hashes.is_empty() {
+        warn!("No stored hashes for {}", path.display());
+        return Ok(VerificationOutcome {
+            path: path.to_path_buf(),
+            missing: false,
+            mismatch: false,
+            failed_algorithm: None,
+            current_size: None,
+            stored_size,
+            current_hash: Vec::new(),
+            stored_hash: Vec::new(),
+            changed_ranges: Vec::new(),
+            skip_report: true,
+        });
+    }
+
+    // Flag an obviously-changed file before paying for the full hash pass:
+    // if the cascade says the current content isn't a member, it can't match
+    // what's stored, so there's no need to run the rest of the pipeline.
+    if path.exists() {
+        if let Some((algorithm, filter)) = cascade {
+            // The cascade's member set only covers rows that actually have a
+            // `CASCADE_ALGORITHM` value (`get_all_hashes` filters out NULLs);
+            // a file hashed into this DB under a different `--algorithm` has
+            // no stored digest to compare against at all, so `!filter.contains`
+            // would be true for every such file regardless of whether its
+            // content changed. Fall through to the normal comparison, which
+            // already reports this case correctly via `NoCommonAlgorithm`.
+            let stored = stored_hashes.iter().find(|h| h.algorithm == *algorithm);
+            if let Some(stored) = stored {
+                if let Some((size, digest)) = quick_digest(path, algorithm, args)? {
+                    if !filter.contains(&digest) {
+                        return Ok(VerificationOutcome {
+                            path: path.to_path_buf(),
+                            missing: false,
+                            mismatch: true,
+                            failed_algorithm: Some(algorithm.to_string()),
+                            current_size: Some(size),
+                            stored_size,
+                            current_hash: digest,
+                            stored_hash: stored.digest.clone(),
+                            changed_ranges: Vec::new(),
+                            skip_report: false,
+                        });
+                    }
+                }
+            }
+        }
     }
 
-    let (current_size, current_hashes) = if !path.exists() {
-        // Check for gzipped version of the file
-        let gz_path = path.with_extension(format!(
-            "{}.gz",
-            path.extension().unwrap_or_default().to_string_lossy()
-        ));
+    let verification = if !path.exists() {
+        // A file recorded uncompressed may since have been stored compressed
+        // under any supported codec (e.g. `foo.txt` -> `foo.txt.zst`); try
+        // every codec's extension rather than assuming gzip.
+        let candidate = compressed_candidates(path)
+            .into_iter()
+            .find(|(candidate_path, _)| candidate_path.exists());
 
-        if gz_path.exists() {
-            _hash_compressed_file(&gz_path, args).await?
+        if let Some((candidate_path, algorithm)) = candidate {
+            _hash_compressed_file(&candidate_path, algorithm, config, args).await?
         } else {
             info!("File not found: {}", path.display());
-            (None, (Vec::new(), Vec::new()))
+            CompressedVerification::Hashed {
+                size: None,
+                hashes: Vec::new(),
+            }
         }
     } else {
-        let compressor = compression::get_compressor(
-            compression::CompressionType::Gzip,
-            args.hash_options.compression_level,
-        );
+        let detected = compression::detect_compression_type(path)?;
+        let is_compressed = detected.is_some();
+        let algorithm = detected.unwrap_or(args.hash_options.compression_algorithm);
 
-        if compressor.is_compressed_path(path) || args.hash_options.hash_compressed {
-            _hash_compressed_file(path, args).await?
+        if is_compressed || args.hash_options.hash_compressed {
+            _hash_compressed_file(path, algorithm, config, args).await?
         } else {
-            _hash_file(path).await?
+            _hash_file(path, config).await?
+        }
+    };
+
+    let (current_size, failed_hash) = match verification {
+        CompressedVerification::ChecksumVerified { size } => {
+            info!(
+                "{}: verified via zstd frame content checksum, sha256 rehash skipped",
+                path.display()
+            );
+            (Some(size), None)
+        }
+        CompressedVerification::Hashed { size, hashes: current_hashes } => {
+            let failed_hash = if size.is_none() {
+                stored_hashes
+                    .first()
+                    .map(|stored| (stored.algorithm.clone(), Vec::new(), stored.digest.clone()))
+            } else {
+                match validate_hashes(&current_hashes, &stored_hashes) {
+                    HashComparison::Match => None,
+                    HashComparison::Mismatch { algorithm, current, stored } => {
+                        Some((algorithm, current, stored))
+                    }
+                    HashComparison::NoCommonAlgorithm => {
+                        warn!("No common hash algorithm for {}", path.display());
+                        return Ok(VerificationOutcome {
+                            path: path.to_path_buf(),
+                            missing: false,
+                            mismatch: false,
+                            failed_algorithm: None,
+                            current_size: size,
+                            stored_size,
+                            current_hash: Vec::new(),
+                            stored_hash: Vec::new(),
+                            changed_ranges: Vec::new(),
+                            skip_report: true,
+                        });
+                    }
+                }
+            };
+            (size, failed_hash)
         }
     };
 
-    let (current_crc32, current_sha256) = current_hashes;
-    let failed_hash = if current_size.is_none() {
-        Some(("crc32".to_string(), Vec::new(), stored_crc32))
+    let missing = current_size.is_none();
+    let mismatch = failed_hash.is_some();
+
+    // Only worth recomputing chunk boundaries when there's a mismatch to
+    // explain and the file is actually present to re-chunk.
+    let changed_ranges = if mismatch && !missing {
+        compute_changed_ranges(config, path, &mut db_conn).await?
     } else {
-        validate_hashes(
-            found_crc32,
-            found_sha256,
-            &current_crc32,
-            &current_sha256,
-            &stored_crc32,
-            &stored_sha256,
-        )
+        Vec::new()
     };
 
-    if failed_hash.is_some() || current_size.is_none() || !args.mismatches_only {
-        let output = build_verification_json(
-            path,
-            current_size,
-            stored_size,
-            failed_hash,
-            current_size.is_none(),
-        );
+    let (failed_algorithm, current_hash, stored_hash) = match failed_hash {
+        Some((algorithm, current, stored)) => (Some(algorithm), current, stored),
+        None => (None, Vec::new(), Vec::new()),
+    };
 
-        if args.hash_options.pretty_json {
-            if let Ok(parsed) = serde_json::from_str::<Value>(&output) {
-                println!("{}", serde_json::to_string_pretty(&parsed).unwrap());
-            } else {
-                println!("{}", output);
-            }
+    Ok(VerificationOutcome {
+        path: path.to_path_buf(),
+        missing,
+        mismatch,
+        failed_algorithm,
+        current_size,
+        stored_size,
+        current_hash,
+        stored_hash,
+        changed_ranges,
+        skip_report: false,
+    })
+}
+
+fn report_outcome(outcome: &VerificationOutcome, args: &HasherVerifyArgs) {
+    let failed_hash = outcome
+        .failed_algorithm
+        .as_ref()
+        .map(|algorithm| (algorithm.clone(), outcome.current_hash.clone(), outcome.stored_hash.clone()));
+
+    let output = build_verification_json(
+        &outcome.path,
+        outcome.current_size,
+        outcome.stored_size,
+        failed_hash,
+        outcome.missing,
+        &outcome.changed_ranges,
+    );
+
+    if args.hash_options.pretty_json {
+        if let Ok(parsed) = serde_json::from_str::<Value>(&output) {
+            println!("{}", serde_json::to_string_pretty(&parsed).unwrap());
         } else {
             println!("{}", output);
         }
+    } else {
+        println!("{}", output);
     }
-
-    Ok(())
 }
 
 pub async fn execute(args: HasherVerifyArgs, config: &Config) -> Result<(), Error> {
     info!("Starting verification");
 
     let mut db_conn = sqlx::SqliteConnection::connect(&config.database.db_string).await?;
+    let paths = get_all_paths(&mut db_conn).await?;
+    drop(db_conn);
+
+    let cascade = match &args.against_cascade {
+        Some(cascade_path) => {
+            let data = std::fs::read_to_string(cascade_path)?;
+            let (algorithm, loaded) = FilterCascade::from_json(&data)?;
+            info!(
+                "Loaded {}-level cascade over {} hashes from {}",
+                loaded.depth(),
+                algorithm,
+                cascade_path.display()
+            );
+            if algorithm != CASCADE_ALGORITHM {
+                warn!(
+                    "Cascade {} was built for {}, but the fast path can only quick-digest {}; \
+                     every file will fall through to the full verification pass",
+                    cascade_path.display(),
+                    algorithm,
+                    CASCADE_ALGORITHM
+                );
+            }
+            Some((algorithm, loaded))
+        }
+        None => None,
+    };
+
+    let jobs = args.hash_options.jobs.max(1);
+    let worker_error: Mutex<Option<Error>> = Mutex::new(None);
+    let args_ref = &args;
+    let cascade_ref = cascade.as_ref();
+
+    // Every path is verified in its own future against its own connection
+    // (verification only reads, so the connections don't block each other), bounded to
+    // `jobs` in flight at once via `buffer_unordered`. Results land in
+    // completion order rather than path order, but since they're only
+    // printed after every task has finished, that's the only thing
+    // interleaved under parallelism - never partial/garbled JSON lines.
+    let results: Vec<Result<VerificationOutcome, (PathBuf, Error)>> = stream::iter(paths.into_iter().map(|path| async move {
+        if worker_error.lock().unwrap().is_some() {
+            return None;
+        }
+        match verify_file(&path, args_ref, config, cascade_ref).await {
+            Ok(outcome) => Some(Ok(outcome)),
+            Err(e) => {
+                if args_ref.hash_options.fail_fast {
+                    worker_error.lock().unwrap().get_or_insert_with(|| e.clone());
+                }
+                Some(Err((path, e)))
+            }
+        }
+    }))
+    .buffer_unordered(jobs)
+    .filter_map(|result| async move { result })
+    .collect()
+    .await;
+
+    if let Some(e) = worker_error.lock().unwrap().take() {
+        return Err(e);
+    }
+
     let mut processed_count = 0;
     let mut missing_count = 0;
     let mut mismatch_count = 0;
     let mut error_count = 0;
 
-    let paths = get_all_paths(&mut db_conn).await?;
-
-    for path in paths {
-        match verify_file(&path, &args, &mut db_conn).await {
-            Ok(()) => {
+    for result in results {
+        match result {
+            Ok(outcome) => {
                 processed_count += 1;
-                if !path.exists() {
+                if outcome.missing {
                     missing_count += 1;
-                } else {
-                    // Check if file was mismatched by re-reading it to validate
-                    if let Ok(stored_hashes) = get_file_hashes(&path, &mut db_conn).await {
-                        let (found_crc32, found_sha256, stored_crc32, stored_sha256, _) =
-                            extract_stored_hashes(&stored_hashes);
-                        let mut hasher = Hasher::new(HashConfig {
-                            crc32: true,
-                            sha256: true,
-                            ..Default::default()
-                        });
-                        if let Ok((_, hashes)) = hasher.hash_file(&path) {
-                            let (current_crc32, current_sha256) = extract_current_hashes(&hashes);
-                            if validate_hashes(
-                                found_crc32,
-                                found_sha256,
-                                &current_crc32,
-                                &current_sha256,
-                                &stored_crc32,
-                                &stored_sha256,
-                            )
-                            .is_some()
-                            {
-                                mismatch_count += 1;
-                            }
-                        }
-                    }
+                }
+                if outcome.mismatch {
+                    mismatch_count += 1;
+                }
+                if !outcome.skip_report && (!outcome.valid() || !args.mismatches_only) {
+                    report_outcome(&outcome, &args);
                 }
             }
-            Err(e) => {
-                let err_msg = format!("Failed to verify {}: {}", path.display(), e);
+            Err((path, e)) => {
                 error_count += 1;
-                if args.hash_options.fail_fast {
-                    return Err(e);
-                }
                 if !args.hash_options.silent_failures {
-                    error!("{}", err_msg);
+                    error!("Failed to verify {}: {}", path.display(), e);
                 }
-                continue;
             }
         }
     }
@@ -5,6 +5,7 @@ use clap::Parser;
 use clap_verbosity_flag::{Verbosity, WarnLevel};
 use serde_derive::Deserialize;
 
+use crate::compression::CompressionType;
 use crate::utils::Error;
 use hasher::HashConfig;
 
@@ -25,6 +26,8 @@ pub enum HasherCommand {
     Verify(HasherVerifyArgs),
     /// Download and hash file at the given URL
     Download(HasherDownloadArgs),
+    /// Export stored hashes as a Bloom filter cascade for offline/fast membership checks
+    Cascade(HasherCascadeArgs),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -96,15 +99,25 @@ pub struct HasherOptions {
     #[arg(short = 'z', long)]
     pub compress: bool,
 
-    /// Compression level (1-9 for gzip)
+    /// Compression level (1-9, whichever algorithm is chosen)
     #[arg(long, default_value_t = 6)]
     #[arg(value_parser = clap::value_parser!(u32).range(1..=9))]
     pub compression_level: u32,
 
+    /// Compression algorithm to use when compressing destination files
+    #[arg(long, value_enum, default_value = "gzip")]
+    pub compression_algorithm: CompressionType,
+
     /// Hash the compressed file instead of uncompressed
     #[arg(short = 'C', long)]
     pub hash_compressed: bool,
 
+    /// Sniff the compression codec from magic bytes instead of trusting the
+    /// file extension; logs a warning and uses the sniffed codec whenever it
+    /// disagrees with the extension (renamed or extensionless files)
+    #[arg(long)]
+    pub detect: bool,
+
     /// Decompress gzipped files before hashing
     #[arg(short = 'x', long)]
     pub decompress: bool,
@@ -116,6 +129,28 @@ pub struct HasherOptions {
     /// Always hash the uncompressed content even when source is compressed
     #[arg(short = 'U', long)]
     pub hash_uncompressed: bool,
+
+    /// Number of files to process concurrently for directory operations
+    /// (copy-and-hash and hash-only); intra-file per-algorithm threading is
+    /// disabled automatically above 1 to avoid oversubscribing cores
+    #[arg(short = 'J', long, default_value_t = 1)]
+    pub jobs: usize,
+
+    /// Additionally split each file into content-defined chunks and store a
+    /// per-chunk SHA256 table in SQLite, enabling block-level dedup
+    #[arg(short = 'K', long)]
+    pub chunked: bool,
+
+    /// During verification, trust a compressed file's own integrity check
+    /// (currently: zstd's frame content checksum) instead of always
+    /// decompressing and rehashing with sha256
+    #[arg(long)]
+    pub trust_codec_integrity: bool,
+
+    /// Fully suppress non-fatal error messages (failed files are still counted
+    /// in the final tally; use --silent-failures if you only want to keep skip messages quiet)
+    #[arg(short = 'f', long)]
+    pub no_messages: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -123,6 +158,13 @@ pub struct HasherHashArgs {
     /// Directory to hash
     pub source: Option<PathBuf>,
 
+    /// Skip files whose path is already present in the database, so an
+    /// interrupted or repeated run only hashes what's new. Unlike counting
+    /// the first N files by walk order, this survives the tree changing
+    /// between runs
+    #[arg(long)]
+    pub resume: bool,
+
     #[clap(flatten)]
     pub hash_options: HasherOptions,
 }
@@ -133,6 +175,30 @@ pub struct HasherVerifyArgs {
     #[arg(short = 'M', long)]
     pub mismatches_only: bool,
 
+    /// Path to a filter cascade exported by `cascade` (see that command);
+    /// when set, a file whose current content isn't a member is flagged as
+    /// changed without paying for the usual full hash pass
+    #[arg(long)]
+    pub against_cascade: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub hash_options: HasherOptions,
+}
+
+#[derive(Parser, Debug)]
+pub struct HasherCascadeArgs {
+    /// Where to write the serialized cascade
+    pub output: PathBuf,
+
+    /// Which stored hash column to build the cascade from (only sha256 is
+    /// accepted)
+    #[arg(long, default_value = "sha256")]
+    pub algorithm: String,
+
+    /// Target false-positive rate for each cascade level
+    #[arg(long, default_value_t = crate::filter::DEFAULT_FALSE_POSITIVE_RATE)]
+    pub false_positive_rate: f64,
+
     #[clap(flatten)]
     pub hash_options: HasherOptions,
 }
@@ -156,6 +222,12 @@ pub struct HasherCopyArgs {
     #[arg(short = 'H', long)]
     pub no_hash_existing: bool,
 
+    /// Pack the whole source tree into a single tar archive at the destination
+    /// instead of mirroring its directory structure (honors --compress for
+    /// .tar.gz/.tar.zst)
+    #[arg(short = 'A', long)]
+    pub archive: bool,
+
     #[clap(flatten)]
     pub hash_options: HasherOptions,
 }
@@ -171,6 +243,25 @@ pub struct HasherDownloadArgs {
     #[arg(short = 'N', long)]
     pub no_clobber: bool,
 
+    /// Maximum number of downloads to run concurrently
+    #[arg(long, default_value_t = 1)]
+    pub max_concurrent: usize,
+
+    /// Aggregate bandwidth cap in bytes/sec, shared across all concurrent downloads
+    #[arg(long)]
+    pub max_bytes_per_sec: Option<u64>,
+
+    /// Stop issuing new downloads once the bytes of finished downloads reach this
+    /// budget; downloads already started still complete, so the total can exceed it
+    #[arg(long)]
+    pub disk_budget: Option<u64>,
+
+    /// Verify each download against an expected digest, given as
+    /// `<algorithm>:<hex digest>` (e.g. `sha256:2c26b46b...`); a mismatch is
+    /// retried like any other failed attempt
+    #[arg(long, value_name = "ALGORITHM:HEX")]
+    pub expected_hash: Option<String>,
+
     #[clap(flatten)]
     pub hash_options: HasherOptions,
 }
@@ -178,6 +269,9 @@ pub struct HasherDownloadArgs {
 #[derive(Deserialize, Default)]
 pub struct Hashes {
     pub crc32: Option<bool>,
+    pub blake3: Option<bool>,
+    pub xxh3: Option<bool>,
+    pub xxh64: Option<bool>,
     pub md2: Option<bool>,
     pub md4: Option<bool>,
     pub md5: Option<bool>,
@@ -250,10 +344,12 @@ pub struct Options {
     pub dry_run: Option<bool>,
     pub compress: Option<bool>,
     pub compression_level: Option<u32>,
+    pub compression_algorithm: Option<CompressionType>,
     pub hash_compressed: Option<bool>,
     pub decompress: Option<bool>,
     pub hash_both: Option<bool>,
     pub hash_uncompressed: Option<bool>,
+    pub no_messages: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -345,6 +441,7 @@ pub fn apply_config_defaults(options: &mut HasherOptions, config: &Config) {
     apply_bool_option!(decompress);
     apply_bool_option!(hash_both);
     apply_bool_option!(hash_uncompressed);
+    apply_bool_option!(no_messages);
 
     // Apply numeric options with their default values
     apply_numeric_option!(retry_count, 3);
@@ -357,12 +454,22 @@ pub fn apply_config_defaults(options: &mut HasherOptions, config: &Config) {
             options.compression_level = level.clamp(1, 9);
         }
     }
+
+    // Special handling for compression_algorithm to only override the default
+    if let Some(algorithm) = cfg_opts.compression_algorithm {
+        if options.compression_algorithm == CompressionType::Gzip {
+            options.compression_algorithm = algorithm;
+        }
+    }
 }
 
 impl From<&Hashes> for HashConfig {
     fn from(hashes: &Hashes) -> Self {
         Self {
             crc32: hashes.crc32.unwrap_or(false),
+            blake3: hashes.blake3.unwrap_or(false),
+            xxh3: hashes.xxh3.unwrap_or(false),
+            xxh64: hashes.xxh64.unwrap_or(false),
             md2: hashes.md2.unwrap_or(false),
             md4: hashes.md4.unwrap_or(false),
             md5: hashes.md5.unwrap_or(false),
@@ -17,6 +17,9 @@ const HASHES: &str = "(
     file_path text not null,
     file_size numeric not null,
     crc32 blob,
+    blake3 blob,
+    xxh3 blob,
+    xxh64 blob,
     md2 blob,
     md4 blob,
     md5 blob,
@@ -66,6 +69,34 @@ const HASHES: &str = "(
     shabal512 blob
 );";
 
+// Chunk table: one row per distinct content-defined chunk, keyed by its
+// strong (SHA256) hash, so identical blocks across files are only ever
+// stored once.
+const CHUNKS: &str = "(
+    chunk_hash blob primary key,
+    length numeric not null
+);";
+
+// Per-file ordered list of chunks, pointing back into the chunk table above.
+const FILE_CHUNKS: &str = "(
+    file_path text not null,
+    chunk_order numeric not null,
+    offset numeric not null,
+    chunk_hash blob not null
+);";
+
+// One row per distinct decompressed content seen by `hash_both`, keyed by the canonical
+// (SHA256) digest of its decompressed content rather than `file_path`, so the
+// same underlying artifact is recognized under any filename or compression.
+// The full hash sets for each state still go to the regular `hashes` table;
+// this table only carries the lookup key and sizes.
+const PAIRED: &str = "(
+    content_name text primary key,
+    file_path text not null,
+    compressed_size numeric not null,
+    decompressed_size numeric not null
+);";
+
 pub async fn init_database(db_string: &str, table_name: &str, use_wal: bool) -> Result<(), Error> {
     info!("Initializing SQLite database;");
     let db_path = db_string.trim_start_matches("sqlite://");
@@ -97,6 +128,47 @@ pub async fn init_database(db_string: &str, table_name: &str, use_wal: bool) ->
 
     info!("Wrote table with name {} to database.", table_name);
 
+    let mut query_builder = QueryBuilder::new("CREATE TABLE IF NOT EXISTS ");
+    query_builder.push(table_name);
+    query_builder.push("_chunks");
+    query_builder.push(CHUNKS);
+    query_builder
+        .build()
+        .execute(&mut db_conn)
+        .await
+        .expect("Failed to create chunks table!");
+
+    let mut query_builder = QueryBuilder::new("CREATE TABLE IF NOT EXISTS ");
+    query_builder.push(table_name);
+    query_builder.push("_file_chunks");
+    query_builder.push(FILE_CHUNKS);
+    query_builder
+        .build()
+        .execute(&mut db_conn)
+        .await
+        .expect("Failed to create file_chunks table!");
+
+    let mut query_builder = QueryBuilder::new("CREATE TABLE IF NOT EXISTS ");
+    query_builder.push(table_name);
+    query_builder.push("_paired");
+    query_builder.push(PAIRED);
+    query_builder
+        .build()
+        .execute(&mut db_conn)
+        .await
+        .expect("Failed to create paired table!");
+
+    let mut query_builder = QueryBuilder::new("CREATE INDEX IF NOT EXISTS ");
+    query_builder.push(table_name);
+    query_builder.push("_file_path_idx ON ");
+    query_builder.push(table_name);
+    query_builder.push("(file_path)");
+    query_builder
+        .build()
+        .execute(&mut db_conn)
+        .await
+        .expect("Failed to create file_path index!");
+
     Ok(())
 }
 
@@ -137,6 +209,93 @@ pub async fn get_file_hashes(
     Ok(results)
 }
 
+// Total row count for the configured table, used to size a resume filter
+// without having to materialize the rows themselves first.
+pub async fn count_rows(config: &Config, db_conn: &mut SqliteConnection) -> Result<i64, Error> {
+    let mut query_builder: QueryBuilder<sqlx::Sqlite> =
+        QueryBuilder::new("SELECT COUNT(*) as count FROM ");
+    query_builder.push(&config.database.table_name);
+
+    let row = query_builder.build().fetch_one(db_conn).await?;
+    Ok(row.get::<i64, _>("count"))
+}
+
+// Single-scan read of every stored path, used to seed a resume filter.
+pub async fn scan_file_paths(
+    config: &Config,
+    db_conn: &mut SqliteConnection,
+) -> Result<Vec<String>, Error> {
+    let mut query_builder: QueryBuilder<sqlx::Sqlite> =
+        QueryBuilder::new("SELECT file_path FROM ");
+    query_builder.push(&config.database.table_name);
+
+    let rows = query_builder.build().fetch_all(db_conn).await?;
+    Ok(rows
+        .iter()
+        .map(|row| row.get::<String, _>("file_path"))
+        .collect())
+}
+
+// Indexed existence check for a single path, used to confirm a resume
+// filter's "maybe present" answers (its false positives make this mandatory;
+// its "definitely absent" answers let callers skip this query entirely).
+pub async fn path_exists(
+    config: &Config,
+    file_path: &Path,
+    db_conn: &mut SqliteConnection,
+) -> Result<bool, Error> {
+    let mut query_builder: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new("SELECT 1 FROM ");
+    query_builder.push(&config.database.table_name);
+    query_builder.push(" WHERE file_path = ");
+    query_builder.push_bind(file_path.display().to_string());
+
+    let row = query_builder.build().fetch_optional(db_conn).await?;
+    Ok(row.is_some())
+}
+
+// Every column in the `hashes` table that can hold a digest, used to
+// allow-list the `--algorithm` argument to `get_all_hashes` before it's
+// interpolated into a query (this one, unlike `config.database.table_name`
+// elsewhere in this file, comes from a flag rather than the config file, so
+// it gets validated rather than trusted).
+const HASH_COLUMNS: &[&str] = &[
+    "crc32", "blake3", "xxh3", "xxh64", "md2", "md4", "md5", "sha1", "sha224", "sha256", "sha384",
+    "sha512", "sha3_224", "sha3_256", "sha3_384", "sha3_512", "keccak224", "keccak256", "keccak384",
+    "keccak512", "blake2s256", "blake2b512", "belt_hash", "whirlpool", "tiger", "tiger2",
+    "streebog256", "streebog512", "ripemd128", "ripemd160", "ripemd256", "ripemd320", "fsb160",
+    "fsb224", "fsb256", "fsb384", "fsb512", "sm3", "gost94_cryptopro", "gost94_test", "gost94_ua",
+    "gost94_s2015", "groestl224", "groestl256", "groestl384", "groestl512", "shabal192", "shabal224",
+    "shabal256", "shabal384", "shabal512",
+];
+
+// Every stored digest for one algorithm across the whole table, used to seed
+// a filter cascade (see commands/cascade.rs) with the complete member set.
+pub async fn get_all_hashes(
+    config: &Config,
+    algorithm: &str,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<Vec<u8>>, Error> {
+    if !HASH_COLUMNS.contains(&algorithm) {
+        return Err(Error::Config(format!("Unknown hash algorithm: {}", algorithm)));
+    }
+
+    let mut query_builder: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new("SELECT ");
+    query_builder.push(algorithm);
+    query_builder.push(" FROM ");
+    query_builder.push(&config.database.table_name);
+    query_builder.push(" WHERE ");
+    query_builder.push(algorithm);
+    query_builder.push(" IS NOT NULL");
+
+    let rows = query_builder.build().fetch_all(conn).await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| row.get::<Option<Vec<u8>>, _>(algorithm))
+        .filter(|hash| !hash.is_empty())
+        .collect())
+}
+
 pub async fn get_all_paths(conn: &mut SqliteConnection) -> Result<Vec<PathBuf>, Error> {
     let query = "SELECT file_path FROM hashes";
 
@@ -195,3 +354,205 @@ pub async fn insert_single_hash(
         }
     }
 }
+
+// SQLite's SQLITE_MAX_VARIABLE_NUMBER defaults to 999 on older builds and
+// 32766 from 3.32.0 onward; batches are sized against the lower, more
+// portable limit so a single multi-row INSERT never overflows it regardless
+// of which libsqlite3 a build links against.
+const SQLITE_MAX_VARIABLES: usize = 999;
+const DEFAULT_BATCH_ROWS: usize = 500;
+
+// Accumulates rows for the `hashes` table and flushes them as a single
+// multi-row `INSERT ... VALUES (...), (...), ...` inside one transaction,
+// so hashing a tree of many small files isn't dominated by per-file
+// round-trips the way `insert_single_hash` is.
+pub struct HashBatch {
+    rows: Vec<(PathBuf, usize, Vec<(&'static str, Vec<u8>)>)>,
+    batch_size: usize,
+}
+
+impl Default for HashBatch {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            batch_size: DEFAULT_BATCH_ROWS,
+        }
+    }
+}
+
+impl HashBatch {
+    pub fn push(&mut self, file_path: PathBuf, size: usize, hashes: Vec<(&'static str, Vec<u8>)>) {
+        let columns_per_row = 2 + hashes.len();
+        let max_rows_for_params = (SQLITE_MAX_VARIABLES / columns_per_row.max(1)).max(1);
+        self.batch_size = self.batch_size.min(max_rows_for_params);
+        self.rows.push((file_path, size, hashes));
+    }
+
+    pub fn should_flush(&self) -> bool {
+        self.rows.len() >= self.batch_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    // Flushes the buffered rows as one transaction. On failure the
+    // transaction is rolled back and the error names every file that was in
+    // the batch, so it's clear which ones still need (re-)hashing (their rows
+    // never reach the table, so a `--resume` run picks them up again).
+    pub async fn flush(&mut self, config: &Config, db_conn: &mut SqliteConnection) -> Result<(), Error> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+
+        let hash_names: Vec<&'static str> =
+            self.rows[0].2.iter().map(|(name, _)| *name).collect();
+
+        let mut query_builder: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new("INSERT INTO ");
+        query_builder.push(&config.database.table_name);
+
+        let mut sep = query_builder.separated(", ");
+        sep.push_unseparated(" (");
+        sep.push("file_path");
+        sep.push("file_size");
+        for name in &hash_names {
+            sep.push(*name);
+        }
+        query_builder.push(") ");
+
+        query_builder.push_values(&self.rows, |mut row, (file_path, size, hashes)| {
+            row.push_bind(file_path.display().to_string());
+            row.push_bind(*size as f64);
+            for (_, hash_data) in hashes {
+                row.push_bind(hash_data.as_slice());
+            }
+        });
+
+        let mut tx = db_conn.begin().await?;
+        if let Err(e) = query_builder.build().execute(&mut *tx).await {
+            tx.rollback().await?;
+            let failed_files: Vec<String> = self
+                .rows
+                .iter()
+                .map(|(path, _, _)| path.display().to_string())
+                .collect();
+            self.rows.clear();
+            return Err(Error::Database(format!(
+                "batch insert of {} file(s) failed, rolled back ({}): {}",
+                failed_files.len(),
+                failed_files.join(", "),
+                e
+            )));
+        }
+        tx.commit().await?;
+
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+// Records a file's content-defined chunk list: one row per chunk in
+// `<table>_file_chunks` (preserving order via `chunk_order`), plus one row
+// per *distinct* chunk in `<table>_chunks` (via `INSERT OR IGNORE` on the
+// chunk's SHA256, which is its primary key) so repeated blocks across files
+// only ever take up space once.
+pub async fn insert_chunked_hash(
+    config: &Config,
+    file_path: &Path,
+    chunks: &[(u64, usize, Vec<(&str, Vec<u8>)>)],
+    db_conn: &mut SqliteConnection,
+) -> Result<(), Error> {
+    let mut tx = db_conn.begin().await?;
+
+    for (order, (offset, length, hashes)) in chunks.iter().enumerate() {
+        let chunk_hash = hashes
+            .iter()
+            .find(|(name, _)| *name == "sha256")
+            .map(|(_, hash)| hash.as_slice())
+            .ok_or_else(|| Error::Config("Chunked hashing requires sha256".to_string()))?;
+
+        let mut insert_chunk: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new("INSERT OR IGNORE INTO ");
+        insert_chunk.push(&config.database.table_name);
+        insert_chunk.push("_chunks (chunk_hash, length) VALUES (");
+        insert_chunk.push_bind(chunk_hash);
+        insert_chunk.push(", ");
+        insert_chunk.push_bind(*length as i64);
+        insert_chunk.push(");");
+        insert_chunk.build().execute(&mut *tx).await?;
+
+        let mut insert_file_chunk: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new("INSERT INTO ");
+        insert_file_chunk.push(&config.database.table_name);
+        insert_file_chunk.push("_file_chunks (file_path, chunk_order, offset, chunk_hash) VALUES (");
+        insert_file_chunk.push_bind(file_path.display().to_string());
+        insert_file_chunk.push(", ");
+        insert_file_chunk.push_bind(order as i64);
+        insert_file_chunk.push(", ");
+        insert_file_chunk.push_bind(*offset as i64);
+        insert_file_chunk.push(", ");
+        insert_file_chunk.push_bind(chunk_hash);
+        insert_file_chunk.push(");");
+        insert_file_chunk.build().execute(&mut *tx).await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// Ordered per-chunk (offset, length, chunk_hash) rows for `file_path`, used
+// by verify's chunked diffing to recompute current chunk boundaries and tell
+// which ones no longer match.
+pub async fn get_file_chunks(
+    config: &Config,
+    file_path: &Path,
+    db_conn: &mut SqliteConnection,
+) -> Result<Vec<(u64, usize, Vec<u8>)>, Error> {
+    let mut query_builder: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new("SELECT fc.offset AS offset, c.length AS length, fc.chunk_hash AS chunk_hash FROM ");
+    query_builder.push(&config.database.table_name);
+    query_builder.push("_file_chunks fc INNER JOIN ");
+    query_builder.push(&config.database.table_name);
+    query_builder.push("_chunks c ON fc.chunk_hash = c.chunk_hash WHERE fc.file_path = ");
+    query_builder.push_bind(file_path.display().to_string());
+    query_builder.push(" ORDER BY fc.chunk_order ASC");
+
+    let rows = query_builder.build().fetch_all(db_conn).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.get::<i64, _>("offset") as u64,
+                row.get::<i64, _>("length") as usize,
+                row.get::<Vec<u8>, _>("chunk_hash"),
+            )
+        })
+        .collect())
+}
+
+// Records a `hash_both` run's compressed/decompressed size pair under
+// `content_name` (the hex SHA256 of the decompressed content), so the same
+// artifact can be looked up regardless of which filename or compression it
+// arrived under. Re-running on the same content is a no-op: `content_name`
+// is the table's primary key, so the file path and compressed size first
+// recorded for it are kept even when a later run sees the same content under
+// another name or codec.
+pub async fn insert_paired_hash(
+    config: &Config,
+    file_path: &Path,
+    content_name: &str,
+    compressed_size: usize,
+    decompressed_size: usize,
+    db_conn: &mut SqliteConnection,
+) -> Result<(), Error> {
+    let mut query_builder: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new("INSERT OR IGNORE INTO ");
+    query_builder.push(&config.database.table_name);
+    query_builder.push("_paired (content_name, file_path, compressed_size, decompressed_size) VALUES (");
+    query_builder.push_bind(content_name.to_string());
+    query_builder.push(", ");
+    query_builder.push_bind(file_path.display().to_string());
+    query_builder.push(", ");
+    query_builder.push_bind(compressed_size as i64);
+    query_builder.push(", ");
+    query_builder.push_bind(decompressed_size as i64);
+    query_builder.push(");");
+    query_builder.build().execute(db_conn).await?;
+    Ok(())
+}
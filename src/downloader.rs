@@ -1,12 +1,20 @@
 use std::io;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use futures::{stream::BoxStream, StreamExt};
 use log::{debug, info, trace, warn};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 
 use crate::compression::{self, CompressionAlgorithm};
+use hasher::{HashConfig, HashResult, Hasher};
+
+// Suffix used for in-progress downloads so interrupted transfers can resume
+const PARTIAL_SUFFIX: &str = ".partial";
 
 #[derive(Clone)]
 pub struct DownloadConfig {
@@ -15,6 +23,17 @@ pub struct DownloadConfig {
     pub compress: bool,
     pub compression_level: u32,
     pub no_clobber: bool,
+    /// Algorithm name and expected digest; the downloaded file is hashed and
+    /// compared against this after each attempt, and mismatches are retried.
+    pub expected_hash: Option<(String, Vec<u8>)>,
+    /// How many downloads `download_from_list` runs at once.
+    pub max_concurrent: usize,
+    /// Aggregate throughput cap shared by every in-flight download.
+    pub max_bytes_per_sec: Option<u64>,
+    /// Stop issuing new downloads once the bytes of finished downloads reach
+    /// this budget; downloads already started still complete, so the total
+    /// can exceed it.
+    pub disk_budget: Option<u64>,
 }
 
 impl Default for DownloadConfig {
@@ -25,6 +44,63 @@ impl Default for DownloadConfig {
             compress: false,
             compression_level: 6,
             no_clobber: false,
+            expected_hash: None,
+            max_concurrent: 1,
+            max_bytes_per_sec: None,
+            disk_budget: None,
+        }
+    }
+}
+
+// Shared rate limiter drawn from by every concurrent download's read loop, so
+// `max_bytes_per_sec` bounds the aggregate throughput rather than each
+// connection individually.
+struct TokenBucket {
+    rate: u64,
+    state: Mutex<TokenBucketState>,
+}
+
+struct TokenBucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            state: Mutex::new(TokenBucketState {
+                tokens: rate as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    async fn take(&self, amount: u64) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.last_refill = now;
+                state.tokens = (state.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+
+                // A take larger than the bucket can ever hold goes through once
+                // the bucket is full, leaving it in debt for later takes to wait off.
+                let needed = (amount as f64).min(self.rate as f64);
+                if state.tokens >= needed {
+                    state.tokens -= amount as f64;
+                    None
+                } else {
+                    let deficit = needed - state.tokens;
+                    Some(Duration::from_secs_f64(deficit / self.rate as f64))
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(delay) => tokio::time::sleep(delay).await,
+            }
         }
     }
 }
@@ -36,11 +112,30 @@ pub struct DownloadResult {
     pub size: u64,
     pub success: bool,
     pub error: Option<String>,
+    /// The digest computed for `expected_hash.0`, once verified to match.
+    pub verified_hash: Option<Vec<u8>>,
+}
+
+// Build a HashConfig with only the named algorithm enabled, so we can reuse
+// the crate's Hasher for a single-digest verification pass.
+fn hash_config_for(algorithm: &str) -> HashConfig {
+    let mut config = HashConfig::default();
+    match algorithm {
+        "crc32" => config.crc32 = true,
+        "md5" => config.md5 = true,
+        "sha1" => config.sha1 = true,
+        "sha256" => config.sha256 = true,
+        "sha384" => config.sha384 = true,
+        "sha512" => config.sha512 = true,
+        other => warn!("Unknown checksum algorithm {}, verification will not match", other),
+    }
+    config
 }
 
 pub struct Downloader {
     client: Client,
     config: DownloadConfig,
+    rate_limiter: Option<Arc<TokenBucket>>,
 }
 
 impl Downloader {
@@ -50,7 +145,9 @@ impl Downloader {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, config }
+        let rate_limiter = config.max_bytes_per_sec.map(|rate| Arc::new(TokenBucket::new(rate)));
+
+        Self { client, config, rate_limiter }
     }
 
     async fn process_download_buffer(&self, buffer: Vec<u8>) -> io::Result<Vec<u8>> {
@@ -72,26 +169,125 @@ impl Downloader {
         .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
     }
 
+    fn partial_path(dest_path: &Path) -> PathBuf {
+        let mut name = dest_path.as_os_str().to_owned();
+        name.push(PARTIAL_SUFFIX);
+        PathBuf::from(name)
+    }
+
+    // Sidecar file recording the remote's ETag/Last-Modified so a resumed
+    // transfer can tell whether the `.partial` bytes still match the remote.
+    fn validator_path(partial_path: &Path) -> PathBuf {
+        let mut name = partial_path.as_os_str().to_owned();
+        name.push(".etag");
+        PathBuf::from(name)
+    }
+
+    fn response_validator(response: &reqwest::Response) -> Option<String> {
+        response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .or_else(|| response.headers().get(reqwest::header::LAST_MODIFIED))
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    }
+
     async fn attempt_download(
         &self,
         url: &str,
         dest_path: &Path,
-    ) -> Result<(u64, PathBuf), Box<dyn std::error::Error>> {
+    ) -> Result<(u64, PathBuf, Vec<u8>), Box<dyn std::error::Error>> {
         debug!("Attempting download of {}", url);
         trace!("Destination path: {}", dest_path.display());
 
-        let response = self.client.get(url).send().await?;
+        let partial_path = Self::partial_path(dest_path);
+        let validator_path = Self::validator_path(&partial_path);
+
+        let mut resume_from = match tokio::fs::metadata(&partial_path).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+
+        let mut request = self.client.get(url);
+        if resume_from > 0 {
+            debug!("Resuming {} from byte {}", url, resume_from);
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+        }
+
+        let mut response = request.send().await?;
         response.error_for_status_ref()?;
 
-        let total_size = response.content_length().unwrap_or(0);
-        debug!("Content length: {} bytes", total_size);
-        let mut downloaded = 0u64;
-        let mut buffer = Vec::new();
+        let remote_validator = Self::response_validator(&response);
+
+        // Figure out whether the server actually honored the Range request.
+        let resuming = resume_from > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+
+        if resume_from > 0 && !resuming {
+            debug!(
+                "Server ignored range request for {} (status {}), restarting from scratch",
+                url,
+                response.status()
+            );
+            resume_from = 0;
+        } else if resuming {
+            // If the remote content changed since we started the partial download,
+            // our bytes no longer line up with what the server will send next.
+            let stored_validator = tokio::fs::read_to_string(&validator_path).await.ok();
+            if stored_validator.is_some() && stored_validator != remote_validator {
+                warn!(
+                    "Remote content for {} changed since partial download started, restarting",
+                    url
+                );
+                resume_from = 0;
+                // The ranged response only holds the tail; fetch the whole body.
+                response = self.client.get(url).send().await?;
+                response.error_for_status_ref()?;
+            }
+        }
+
+        let mut file = if resume_from > 0 {
+            OpenOptions::new().append(true).open(&partial_path).await?
+        } else {
+            let mut file = File::create(&partial_path).await?;
+            file.set_len(0).await?;
+            file
+        };
+        file.seek(io::SeekFrom::End(0)).await?;
+
+        if let Some(validator) = &remote_validator {
+            tokio::fs::write(&validator_path, validator).await?;
+        }
+
+        // Hash the file as its bytes arrive instead of re-reading it from disk
+        // afterwards, so verification never doubles the peak memory of a
+        // download. Fed with the already-on-disk partial bytes first (in
+        // bounded chunks, not a single read) so a resumed download still
+        // produces a digest over the whole file.
+        let mut live_hasher = match self.config.expected_hash.as_ref() {
+            Some((algorithm, _)) if resume_from > 0 => {
+                let mut hasher = Hasher::new(hash_config_for(algorithm));
+                Self::feed_existing_partial(&mut hasher, &partial_path, resume_from).await?;
+                Some(hasher)
+            }
+            Some((algorithm, _)) => Some(Hasher::new(hash_config_for(algorithm))),
+            None => None,
+        };
+
+        let total_size = response.content_length().unwrap_or(0) + resume_from;
+        debug!("Content length: {} bytes (resuming from {})", total_size, resume_from);
+
+        let mut downloaded = resume_from;
         let mut stream = response.bytes_stream();
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
-            buffer.extend_from_slice(&chunk);
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.take(chunk.len() as u64).await;
+            }
+            file.write_all(&chunk).await?;
+            if let Some(hasher) = &mut live_hasher {
+                hasher.update(&chunk).map_err(|e| e.to_string())?;
+            }
             downloaded += chunk.len() as u64;
 
             if downloaded % (5 * 1024 * 1024) == 0 {
@@ -99,17 +295,93 @@ impl Downloader {
             }
             trace!("Downloaded chunk of {} bytes", chunk.len());
         }
+        file.flush().await?;
+        drop(file);
+
+        // Verify the checksum against the `.partial` bytes before anything is
+        // renamed onto `dest_path`, so a mismatch never leaves a corrupt file
+        // where a later run's `--no-clobber` check would mistake it for a
+        // good one and skip re-downloading forever.
+        let digest = live_hasher
+            .map(|mut hasher| hasher.finalize())
+            .transpose()
+            .map_err(|e| e.to_string())?;
+
+        let verified_hash = match self.verify_checksum(digest) {
+            Ok(verified) => verified,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial_path).await;
+                let _ = tokio::fs::remove_file(&validator_path).await;
+                return Err(e.into());
+            }
+        };
 
         debug!("Download complete, processing buffer");
-        let processed_buffer = self.process_download_buffer(buffer).await?;
-        debug!(
-            "Writing {} bytes to {}",
-            processed_buffer.len(),
-            dest_path.display()
-        );
-        tokio::fs::write(dest_path, processed_buffer).await?;
+        if self.config.compress {
+            let raw = tokio::fs::read(&partial_path).await?;
+            let processed_buffer = self.process_download_buffer(raw).await?;
+            debug!(
+                "Writing {} bytes to {}",
+                processed_buffer.len(),
+                dest_path.display()
+            );
+            tokio::fs::write(dest_path, processed_buffer).await?;
+            tokio::fs::remove_file(&partial_path).await?;
+        } else {
+            tokio::fs::rename(&partial_path, dest_path).await?;
+        }
+        let _ = tokio::fs::remove_file(&validator_path).await;
 
-        Ok((downloaded, dest_path.to_path_buf()))
+        Ok((downloaded, dest_path.to_path_buf(), verified_hash))
+    }
+
+    // Feed a live hasher with the bytes an earlier attempt already wrote to
+    // the `.partial` file, so a resumed download still yields a digest over
+    // the whole file without ever holding it fully in memory.
+    async fn feed_existing_partial(
+        hasher: &mut Hasher,
+        partial_path: &Path,
+        len: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use tokio::io::AsyncReadExt;
+
+        let mut reader = File::open(partial_path).await?;
+        let mut buf = vec![0u8; 1024 * 1024];
+        let mut remaining = len;
+
+        while remaining > 0 {
+            let to_read = remaining.min(buf.len() as u64) as usize;
+            reader.read_exact(&mut buf[..to_read]).await?;
+            hasher.update(&buf[..to_read]).map_err(|e| e.to_string())?;
+            remaining -= to_read as u64;
+        }
+
+        Ok(())
+    }
+
+    // Compare a freshly-computed digest against the expected hash, returning
+    // the verified bytes on a match.
+    fn verify_checksum(&self, digest: Option<HashResult>) -> Result<Vec<u8>, String> {
+        let Some((algorithm, expected)) = &self.config.expected_hash else {
+            return Ok(Vec::new());
+        };
+        let digest = digest.ok_or_else(|| "No digest computed for download".to_string())?;
+
+        let digest = digest
+            .into_iter()
+            .find(|(name, _)| name == algorithm)
+            .map(|(_, bytes)| bytes)
+            .ok_or_else(|| format!("Unknown checksum algorithm: {}", algorithm))?;
+
+        if &digest != expected {
+            return Err(format!(
+                "Checksum mismatch: expected {}, got {}",
+                hex::encode(expected),
+                hex::encode(&digest)
+            ));
+        }
+
+        Ok(digest)
     }
 
     pub async fn download_file(&self, url: String, dest_path: PathBuf) -> DownloadResult {
@@ -120,6 +392,7 @@ impl Downloader {
             size: 0,
             success: false,
             error: None,
+            verified_hash: None,
         };
 
         // Check if file exists when no-clobber is enabled
@@ -150,10 +423,11 @@ impl Downloader {
             }
 
             match self.attempt_download(&result.url, &result.path).await {
-                Ok((size, final_path)) => {
+                Ok((size, final_path, verified_hash)) => {
                     result.size = size;
                     result.path = final_path;
                     result.success = true;
+                    result.verified_hash = (!verified_hash.is_empty()).then_some(verified_hash);
                     break;
                 }
                 Err(e) => {
@@ -193,17 +467,42 @@ impl Downloader {
                     size: 0,
                     success: false,
                     error: Some(format!("Failed to create directory: {}", e)),
+                    verified_hash: None,
                 }
             })
             .boxed();
         }
 
+        let disk_used = Arc::new(AtomicU64::new(0));
+
         futures::stream::iter(urls)
             .map(move |url| {
                 let dest_path = dest_dir.join(filename_fn(&url));
-                async move { self.download_file(url, dest_path).await }
+                let disk_used = disk_used.clone();
+                async move {
+                    if let Some(budget) = self.config.disk_budget {
+                        if disk_used.load(Ordering::Relaxed) >= budget {
+                            debug!(
+                                "Disk budget of {} bytes reached, skipping {}",
+                                budget, url
+                            );
+                            return DownloadResult {
+                                url,
+                                path: dest_path,
+                                size: 0,
+                                success: false,
+                                error: Some("Skipped: disk budget reached".to_string()),
+                                verified_hash: None,
+                            };
+                        }
+                    }
+
+                    let result = self.download_file(url, dest_path).await;
+                    disk_used.fetch_add(result.size, Ordering::Relaxed);
+                    result
+                }
             })
-            .buffer_unordered(1)
+            .buffer_unordered(self.config.max_concurrent.max(1))
             .boxed()
     }
 }
@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+
+use bloomfilter::Bloom;
+use serde_json::{json, Value};
+
+use crate::utils::Error;
+
+// Chosen low enough that a cascade typically bottoms out within a handful
+// of levels (see `FilterCascade::build`) without ballooning the exported
+// file size.
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 1e-3;
+
+// The only algorithm verify's cascade fast path can currently quick-digest a
+// file against without decompressing/rehashing it (see `quick_digest` in
+// commands/verify.rs). `cascade` validates `--algorithm` against this so a
+// cascade can't silently be built for an algorithm the fast path will never
+// use.
+pub const CASCADE_ALGORITHM: &str = "sha256";
+
+// Hard cap on cascade depth so a pathological member/negative split can't
+// loop forever; past this point the cascade just keeps whatever
+// false-positive rate the last level landed on. Odd, so a capped build ends
+// on an even (member-built) level and its leftover errors stay false
+// positives rather than false negatives.
+const MAX_LEVELS: usize = 13;
+
+// Minimal splitmix64 generator (Vigna, public domain) - avoids pulling in a
+// `rand` dependency just to synthesize negative probes below.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+// Synthesizes `count` digest-shaped byte strings guaranteed not to be in
+// `members`, standing in for the "hashes you scanned that aren't in R"
+// negative corpus the cascade construction needs in order to correct level
+// 0's false positives. A uniformly random probe of the same length as a real
+// digest is a sound stand-in here specifically because digests already look
+// uniformly random - unlike e.g. URL strings, there's no structure in real
+// non-member queries that a random sample would fail to represent.
+pub fn synthesize_negative_probes(members: &[Vec<u8>], count: usize, digest_len: usize, seed: u64) -> Vec<Vec<u8>> {
+    let member_set: HashSet<&Vec<u8>> = members.iter().collect();
+    let mut rng = SplitMix64(seed);
+    let mut negatives = Vec::with_capacity(count);
+
+    while negatives.len() < count {
+        let mut digest = Vec::with_capacity(digest_len);
+        while digest.len() < digest_len {
+            digest.extend_from_slice(&rng.next_u64().to_le_bytes());
+        }
+        digest.truncate(digest_len);
+
+        if !member_set.contains(&digest) {
+            negatives.push(digest);
+        }
+    }
+
+    negatives
+}
+
+// A zero-false-negative membership structure for a fixed set R of digests.
+// Level 0 is a plain Bloom filter over R. Each subsequent level is built
+// from, and corrects, the previous level's false positives: querying the
+// negative corpus against an even level yields the negatives it wrongly
+// claims as members (set R_n+1, fed into the next level); querying R against
+// an odd level yields the members it wrongly claims as negatives (set
+// U_n+1, fed into the level after that). Construction stops once a level
+// produces no false positives of its own. Querying walks the levels in
+// order: absent at level 0 means not a member at all; otherwise the parity
+// of the deepest level at which the item is still present decides
+// membership (present through an even-numbered depth means it's a real
+// member).
+pub struct FilterCascade {
+    levels: Vec<Bloom<Vec<u8>>>,
+}
+
+impl FilterCascade {
+    pub fn build(members: &[Vec<u8>], negatives: &[Vec<u8>], false_positive_rate: f64) -> Self {
+        let mut levels: Vec<Bloom<Vec<u8>>> = Vec::new();
+        let mut level_items: Vec<Vec<u8>> = members.to_vec();
+
+        loop {
+            let mut filter = Bloom::new_for_fp_rate(level_items.len().max(1), false_positive_rate);
+            for item in &level_items {
+                filter.set(item);
+            }
+
+            // Even levels were built from (a subset of) true members, so
+            // they get tested against the negative corpus; odd levels were
+            // built from (a subset of) negatives, so they get tested
+            // against the true members - whichever set this level's own
+            // contents didn't come from.
+            let test_set = if levels.len() % 2 == 0 { negatives } else { members };
+            let false_positives: Vec<Vec<u8>> =
+                test_set.iter().filter(|item| filter.check(item)).cloned().collect();
+
+            levels.push(filter);
+
+            if false_positives.is_empty() || levels.len() >= MAX_LEVELS {
+                break;
+            }
+            level_items = false_positives;
+        }
+
+        Self { levels }
+    }
+
+    pub fn contains(&self, item: &[u8]) -> bool {
+        let item = item.to_vec();
+        let mut present_depth = None;
+
+        for (depth, filter) in self.levels.iter().enumerate() {
+            if filter.check(&item) {
+                present_depth = Some(depth);
+            } else {
+                break;
+            }
+        }
+
+        matches!(present_depth, Some(depth) if depth % 2 == 0)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn to_json(&self, algorithm: &str) -> String {
+        let levels: Vec<Value> = self
+            .levels
+            .iter()
+            .map(|filter| {
+                let sip_keys = filter.sip_keys();
+                json!({
+                    "bitmap_bits": filter.number_of_bits(),
+                    "k_num": filter.number_of_hash_functions(),
+                    "sip_keys": [[sip_keys[0].0, sip_keys[0].1], [sip_keys[1].0, sip_keys[1].1]],
+                    "bitmap": hex::encode(filter.bitmap()),
+                })
+            })
+            .collect();
+
+        json!({ "algorithm": algorithm, "levels": levels }).to_string()
+    }
+
+    pub fn from_json(data: &str) -> Result<(String, Self), Error> {
+        let parsed: Value =
+            serde_json::from_str(data).map_err(|e| Error::Config(format!("Invalid cascade file: {}", e)))?;
+
+        let algorithm = parsed["algorithm"]
+            .as_str()
+            .ok_or_else(|| Error::Config("Cascade file missing \"algorithm\"".to_string()))?
+            .to_string();
+
+        let levels_json = parsed["levels"]
+            .as_array()
+            .ok_or_else(|| Error::Config("Cascade file missing \"levels\"".to_string()))?;
+
+        let levels = levels_json
+            .iter()
+            .map(parse_level)
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok((algorithm, Self { levels }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthesize_negative_probes_excludes_members_and_matches_shape() {
+        let members: Vec<Vec<u8>> = (0u8..10).map(|b| vec![b; 4]).collect();
+        let negatives = synthesize_negative_probes(&members, 50, 4, 42);
+
+        assert_eq!(negatives.len(), 50);
+        for negative in &negatives {
+            assert_eq!(negative.len(), 4);
+            assert!(!members.contains(negative));
+        }
+    }
+
+    // The cascade's whole point is to never report a true member as absent;
+    // this is a structural guarantee of the even/odd correction levels, not
+    // a statistical one, so it should hold regardless of how many levels the
+    // build ends up needing.
+    #[test]
+    fn test_cascade_never_reports_a_false_negative() {
+        let members: Vec<Vec<u8>> = (0u32..200).map(|i| i.to_le_bytes().to_vec()).collect();
+        let negatives = synthesize_negative_probes(&members, 2000, 4, 0xC0FFEE);
+
+        let cascade = FilterCascade::build(&members, &negatives, DEFAULT_FALSE_POSITIVE_RATE);
+
+        for member in &members {
+            assert!(cascade.contains(member), "member {:?} incorrectly reported absent", member);
+        }
+
+        // Unlike the false-negative guarantee above, false positives on the
+        // negative corpus are only bounded probabilistically - just check
+        // the rate is in the right ballpark rather than asserting an exact
+        // figure, so this doesn't become a flaky test.
+        let false_positives = negatives.iter().filter(|n| cascade.contains(n)).count();
+        assert!(
+            (false_positives as f64) < negatives.len() as f64 * 0.5,
+            "false positive rate on negatives ({}/{}) is far higher than expected",
+            false_positives,
+            negatives.len()
+        );
+    }
+
+    #[test]
+    fn test_cascade_json_round_trip_preserves_membership() {
+        let members: Vec<Vec<u8>> = (0u32..50).map(|i| i.to_le_bytes().to_vec()).collect();
+        let negatives = synthesize_negative_probes(&members, 500, 4, 7);
+        let cascade = FilterCascade::build(&members, &negatives, DEFAULT_FALSE_POSITIVE_RATE);
+
+        let json = cascade.to_json(CASCADE_ALGORITHM);
+        let (algorithm, loaded) = FilterCascade::from_json(&json).unwrap();
+
+        assert_eq!(algorithm, CASCADE_ALGORITHM);
+        assert_eq!(loaded.depth(), cascade.depth());
+        for member in &members {
+            assert!(loaded.contains(member));
+        }
+    }
+
+    #[test]
+    fn test_cascade_from_json_rejects_malformed_input() {
+        assert!(FilterCascade::from_json("not json").is_err());
+        assert!(FilterCascade::from_json(r#"{"levels":[]}"#).is_err());
+        assert!(FilterCascade::from_json(r#"{"algorithm":"sha256"}"#).is_err());
+    }
+}
+
+fn parse_level(level: &Value) -> Result<Bloom<Vec<u8>>, Error> {
+    let malformed = || Error::Config("Cascade level is malformed".to_string());
+
+    let bitmap_bits = level["bitmap_bits"].as_u64().ok_or_else(malformed)?;
+    let k_num = level["k_num"].as_u64().ok_or_else(malformed)? as u32;
+
+    let sip_keys_json = level["sip_keys"].as_array().ok_or_else(malformed)?;
+    if sip_keys_json.len() != 2 {
+        return Err(malformed());
+    }
+    let parse_pair = |v: &Value| -> Result<(u64, u64), Error> {
+        let pair = v.as_array().ok_or_else(malformed)?;
+        if pair.len() != 2 {
+            return Err(malformed());
+        }
+        Ok((pair[0].as_u64().ok_or_else(malformed)?, pair[1].as_u64().ok_or_else(malformed)?))
+    };
+    let sip_keys = [parse_pair(&sip_keys_json[0])?, parse_pair(&sip_keys_json[1])?];
+
+    let bitmap_hex = level["bitmap"].as_str().ok_or_else(malformed)?;
+    let bitmap = hex::decode(bitmap_hex).map_err(|_| malformed())?;
+
+    Ok(Bloom::from_existing(&bitmap, bitmap_bits, k_num, sip_keys))
+}
@@ -13,7 +13,9 @@ mod compression;
 mod configuration;
 mod database;
 mod downloader;
+mod filter;
 mod output;
+mod resume;
 mod utils;
 
 fn setup_logging<T: clap_verbosity_flag::LogLevel>(verbose: &Verbosity<T>) {
@@ -41,6 +43,7 @@ async fn main() {
         HasherCommand::Copy(args) => (&args.hash_options, &args.hash_options.config_file),
         HasherCommand::Verify(args) => (&args.hash_options, &args.hash_options.config_file),
         HasherCommand::Download(args) => (&args.hash_options, &args.hash_options.config_file),
+        HasherCommand::Cascade(args) => (&args.hash_options, &args.hash_options.config_file),
     };
 
     setup_logging(&hash_options.verbose);
@@ -101,7 +104,7 @@ async fn main() {
             }
             false // No need to close WAL since we didn't enable it
         }
-        HasherCommand::Verify(_) | HasherCommand::Copy(_) => {
+        HasherCommand::Verify(_) | HasherCommand::Copy(_) | HasherCommand::Cascade(_) => {
             // These commands always need database access
             if let Err(e) = database::init_database(
                 &config.database.db_string,
@@ -122,6 +125,7 @@ async fn main() {
         HasherCommand::Copy(args) => commands::copy::execute(args, &config).await,
         HasherCommand::Verify(args) => commands::verify::execute(args, &config).await,
         HasherCommand::Download(args) => commands::download::execute(args, &config).await.map(|_| ()),
+        HasherCommand::Cascade(args) => commands::cascade::execute(args, &config).await,
     };
 
     if let Err(e) = result {
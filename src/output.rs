@@ -1,21 +1,89 @@
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use log::{error, info};
+use futures::stream::{self, StreamExt};
+use log::{error, info, warn};
 use serde_json::json;
 use sqlx::{Connection, SqliteConnection};
+use tokio::sync::mpsc;
 use walkdir::WalkDir;
 
 use crate::compression::{self, CompressionAlgorithm};
 use crate::configuration::{Config, HasherOptions};
-use crate::database::insert_single_hash;
+use crate::database::{insert_chunked_hash, insert_paired_hash, insert_single_hash, HashBatch};
+use crate::resume::ResumeFilter;
 use crate::utils::Error;
-use hasher::{HashConfig, Hasher};
+use hasher::{HashConfig, Hasher, HashResult};
+
+// One content-defined chunk as produced by `Hasher::hash_file_chunked`:
+// (offset, length, per-chunk hashes).
+type ChunkList = Vec<(u64, usize, HashResult)>;
+
+// The `_paired` table lookup key plus the sizes it was recorded with, carried
+// alongside a `hash_both` run's *decompressed* row (where the canonical
+// digest naturally lives) on its way to the DB writer.
+struct PairedInfo {
+    content_name: String,
+    compressed_size: usize,
+    decompressed_size: usize,
+}
+
+// A finished hash, on its way from the walker in `process_directory` to the
+// single task below that owns the DB connection.
+struct HashRecord {
+    path: PathBuf,
+    size: usize,
+    hashes: HashResult,
+    chunks: Option<ChunkList>,
+    paired: Option<PairedInfo>,
+}
+
+// Where a processed file's row goes: handed off over a channel to the
+// dedicated writer task (directory walks, see `process_directory`), or
+// nowhere (`None`, which the download command passes, so the files it
+// hashes get no database row).
+pub enum HashWriter<'a> {
+    None,
+    Channel(&'a mpsc::Sender<HashRecord>),
+}
+
+impl HashWriter<'_> {
+    async fn write(
+        &mut self,
+        _config: &Config,
+        file_path: &Path,
+        size: usize,
+        hashes: &[(&'static str, Vec<u8>)],
+        chunks: Option<ChunkList>,
+        paired: Option<PairedInfo>,
+    ) -> Result<(), Error> {
+        match self {
+            HashWriter::None => Ok(()),
+            HashWriter::Channel(tx) => {
+                // The receiver only closes once the walker has dropped its
+                // sender, so this can't fail before `process_directory` is
+                // done waiting on it.
+                let _ = tx
+                    .send(HashRecord {
+                        path: file_path.to_path_buf(),
+                        size,
+                        hashes: hashes.to_vec(),
+                        chunks,
+                        paired,
+                    })
+                    .await;
+                Ok(())
+            }
+        }
+    }
+}
 
 fn build_hash_json(
     file_path: &Path,
     file_size: usize,
     hashes: &[(&str, Vec<u8>)],
+    chunks: Option<&ChunkList>,
 ) -> serde_json::Map<String, serde_json::Value> {
     let mut hash_map = serde_json::Map::new();
     hash_map.insert(
@@ -28,6 +96,22 @@ fn build_hash_json(
         hash_map.insert(hash_name.to_string(), json!(hex::encode(hash_data)));
     }
 
+    if let Some(chunks) = chunks {
+        let chunks_json: Vec<serde_json::Value> = chunks
+            .iter()
+            .map(|(offset, size, hashes)| {
+                let mut chunk_map = serde_json::Map::new();
+                chunk_map.insert("offset".to_string(), json!(offset));
+                chunk_map.insert("size".to_string(), json!(size));
+                for (hash_name, hash_data) in hashes {
+                    chunk_map.insert(hash_name.to_string(), json!(hex::encode(hash_data)));
+                }
+                serde_json::Value::Object(chunk_map)
+            })
+            .collect();
+        hash_map.insert("chunks".to_string(), json!(chunks_json));
+    }
+
     hash_map
 }
 
@@ -35,9 +119,74 @@ fn output_json(
     file_path: &Path,
     file_size: usize,
     hashes: &[(&str, Vec<u8>)],
+    chunks: Option<&ChunkList>,
+    pretty: bool,
+) -> Option<serde_json::Map<String, serde_json::Value>> {
+    let hash_map = build_hash_json(file_path, file_size, hashes, chunks);
+
+    let output = if pretty {
+        serde_json::to_string_pretty(&hash_map)
+    } else {
+        serde_json::to_string(&hash_map)
+    }
+    .unwrap();
+
+    println!("{}", output);
+    Some(hash_map)
+}
+
+// Builds the single merged record a `hash_both` run produces: the compressed
+// and decompressed states nested under their own keys, tied together by
+// `content_name` (the hex digest `hash_both` named the pair with), rather
+// than the two loosely related top-level objects a plain run emits.
+fn build_paired_hash_json(
+    file_path: &Path,
+    compressed_size: usize,
+    compressed_hashes: &[(&str, Vec<u8>)],
+    decompressed_size: usize,
+    decompressed_hashes: &[(&str, Vec<u8>)],
+    content_name: &str,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut compressed = serde_json::Map::new();
+    compressed.insert("size".to_string(), json!(compressed_size));
+    for (hash_name, hash_data) in compressed_hashes {
+        compressed.insert(hash_name.to_string(), json!(hex::encode(hash_data)));
+    }
+
+    let mut decompressed = serde_json::Map::new();
+    decompressed.insert("size".to_string(), json!(decompressed_size));
+    for (hash_name, hash_data) in decompressed_hashes {
+        decompressed.insert(hash_name.to_string(), json!(hex::encode(hash_data)));
+    }
+
+    let mut hash_map = serde_json::Map::new();
+    hash_map.insert(
+        "file_path".to_string(),
+        json!(file_path.display().to_string()),
+    );
+    hash_map.insert("compressed".to_string(), serde_json::Value::Object(compressed));
+    hash_map.insert("decompressed".to_string(), serde_json::Value::Object(decompressed));
+    hash_map.insert("content_name".to_string(), json!(content_name));
+    hash_map
+}
+
+fn output_paired_json(
+    file_path: &Path,
+    compressed_size: usize,
+    compressed_hashes: &[(&str, Vec<u8>)],
+    decompressed_size: usize,
+    decompressed_hashes: &[(&str, Vec<u8>)],
+    content_name: &str,
     pretty: bool,
 ) -> Option<serde_json::Map<String, serde_json::Value>> {
-    let hash_map = build_hash_json(file_path, file_size, hashes);
+    let hash_map = build_paired_hash_json(
+        file_path,
+        compressed_size,
+        compressed_hashes,
+        decompressed_size,
+        decompressed_hashes,
+        content_name,
+    );
 
     let output = if pretty {
         serde_json::to_string_pretty(&hash_map)
@@ -50,6 +199,18 @@ fn output_json(
     Some(hash_map)
 }
 
+// The hex SHA256 of `hashes`, the canonical digest `hash_both` names paired
+// content with. Chosen for the same reason `insert_chunked_hash` requires
+// SHA256 for chunk keys: it's the strong identity hash this tree keys
+// lookups on. Fails if the configured hashes don't include sha256.
+fn content_name_from(hashes: &[(&'static str, Vec<u8>)]) -> Result<String, Error> {
+    hashes
+        .iter()
+        .find(|(name, _)| *name == "sha256")
+        .map(|(_, hash)| hex::encode(hash))
+        .ok_or_else(|| Error::Config("hash_both requires sha256 to name paired content".to_string()))
+}
+
 fn log_hash_results(file_path: &Path, hashes: &[(&str, Vec<u8>)]) {
     info!("Successfully hashed {}", file_path.display());
     for (name, hash) in hashes {
@@ -61,9 +222,10 @@ async fn store_hash_results(
     config: &Config,
     file_path: &Path,
     size: usize,
-    hashes: &[(&str, Vec<u8>)],
+    hashes: &[(&'static str, Vec<u8>)],
+    chunks: Option<ChunkList>,
     args: &HasherOptions,
-    db_conn: &mut Option<SqliteConnection>,
+    writer: &mut HashWriter<'_>,
 ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, Error> {
     if args.dry_run {
         return Ok(None);
@@ -73,38 +235,152 @@ async fn store_hash_results(
     let do_json = !args.sql_only;
 
     if do_sql {
-        if let Some(conn) = db_conn {
-            insert_single_hash(config, file_path, size, hashes, conn).await?;
-        }
+        writer.write(config, file_path, size, hashes, chunks.clone(), None).await?;
+    }
+
+    if do_json {
+        Ok(output_json(file_path, size, hashes, chunks.as_ref(), args.pretty_json))
+    } else {
+        Ok(None)
+    }
+}
+
+// `hash_both` counterpart to `store_hash_results`: writes the compressed and
+// decompressed rows to the database (the decompressed row additionally
+// carrying the `_paired` lookup key), then emits the two states as one
+// merged JSON record instead of `store_hash_results`'s single flat one.
+#[allow(clippy::too_many_arguments)]
+async fn store_paired_hash_results(
+    config: &Config,
+    file_path: &Path,
+    compressed_size: usize,
+    compressed_hashes: &HashResult,
+    decompressed_size: usize,
+    decompressed_hashes: &HashResult,
+    args: &HasherOptions,
+    writer: &mut HashWriter<'_>,
+) -> Result<Option<serde_json::Map<String, serde_json::Value>>, Error> {
+    if args.dry_run {
+        return Ok(None);
+    }
+
+    let content_name = content_name_from(decompressed_hashes)?;
+    let do_sql = !args.json_only;
+    let do_json = !args.sql_only;
+
+    if do_sql {
+        writer
+            .write(config, file_path, compressed_size, compressed_hashes, None, None)
+            .await?;
+        let decomp_path = file_path.with_extension("");
+        writer
+            .write(
+                config,
+                &decomp_path,
+                decompressed_size,
+                decompressed_hashes,
+                None,
+                Some(PairedInfo {
+                    content_name: content_name.clone(),
+                    compressed_size,
+                    decompressed_size,
+                }),
+            )
+            .await?;
     }
 
     if do_json {
-        Ok(output_json(file_path, size, hashes, args.pretty_json))
+        Ok(output_paired_json(
+            file_path,
+            compressed_size,
+            compressed_hashes,
+            decompressed_size,
+            decompressed_hashes,
+            &content_name,
+            args.pretty_json,
+        ))
     } else {
         Ok(None)
     }
 }
 
+// Splits `path` into content-defined chunks (FastCDC via `hash_file_chunked`)
+// and hashes each one; only SHA256 is computed per chunk since that's the
+// strong hash the chunk table is keyed on (mirrors `chunk_file` in
+// commands/copy.rs, which does the same thing for the copy-and-hash path).
+fn chunk_file(path: &Path, args: &HasherOptions) -> Result<ChunkList, Error> {
+    let mut chunk_hasher = Hasher::new(HashConfig {
+        sha256: true,
+        ..Default::default()
+    });
+    match chunk_hasher.hash_file_chunked(path) {
+        Ok((_, chunks)) => Ok(chunks),
+        Err(e) if !args.fail_fast => {
+            error!("Failed to chunk {}: {}", path.display(), e);
+            Ok(Vec::new())
+        }
+        Err(e) => Err(Error::from(e)),
+    }
+}
+
+// Decides whether `file_path` should be treated as compressed, and which
+// codec applies to data already on disk. Extension-only (the default)
+// assumes `args.compression_algorithm` for every compressed-looking path, the
+// same as before `--detect` existed. With `--detect`, the file's leading
+// bytes are sniffed via `compression::detect_compression_type`; a mismatch
+// against the extension is logged and the sniffed codec wins, since it
+// reflects what's actually on disk rather than what the name claims.
+fn resolve_compression(
+    file_path: &Path,
+    args: &HasherOptions,
+) -> Result<(bool, compression::CompressionType), Error> {
+    if !args.detect {
+        let compressor = compression::get_compressor(args.compression_algorithm, args.compression_level);
+        return Ok((compressor.is_compressed_path(file_path), args.compression_algorithm));
+    }
+
+    match compression::detect_compression_type(file_path) {
+        Ok(Some(detected)) => {
+            if let Some(from_extension) = compression::detect_compression_type_from_extension(file_path) {
+                if from_extension != detected {
+                    warn!(
+                        "{}: magic bytes indicate {:?} but the extension suggests {:?}; trusting magic bytes",
+                        file_path.display(),
+                        detected,
+                        from_extension
+                    );
+                }
+            }
+            Ok((true, detected))
+        }
+        Ok(None) => Ok((false, args.compression_algorithm)),
+        // The file may not exist yet (e.g. a fresh compression target), so
+        // fall back to the extension-only check rather than failing outright.
+        Err(_) => {
+            let compressor = compression::get_compressor(args.compression_algorithm, args.compression_level);
+            Ok((compressor.is_compressed_path(file_path), args.compression_algorithm))
+        }
+    }
+}
+
 async fn process_compressed_file(
     file_path: &Path,
     config: &Config,
     args: &HasherOptions,
-    db_conn: &mut Option<SqliteConnection>,
+    algorithm: compression::CompressionType,
+    already_compressed: bool,
+    writer: &mut HashWriter<'_>,
 ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, Error> {
-    let compressor =
-        compression::get_compressor(compression::CompressionType::Gzip, args.compression_level);
-
-    // Read or compress the file data
-    let compressed_data = if compressor.is_compressed_path(file_path) {
+    // Read or compress the file data. Data we compress ourselves is wrapped in
+    // a self-describing envelope so the decompression steps below don't have
+    // to assume an algorithm; data already on disk keeps its own file format
+    // and is decompressed using `algorithm` (the configured codec, or the one
+    // `--detect` sniffed from its magic bytes).
+    let compressed_data = if already_compressed {
         tokio::fs::read(file_path).await?
     } else {
         let data = tokio::fs::read(file_path).await?;
-        compression::compress_bytes(
-            &data,
-            compression::CompressionType::Gzip,
-            args.compression_level,
-        )
-        .map_err(Error::from)?
+        compression::compress_envelope(&data, algorithm, args.compression_level).map_err(Error::from)?
     };
 
     // Create a hasher with the config
@@ -115,53 +391,47 @@ async fn process_compressed_file(
         let comp_hashes = hasher.hash_single_buffer(&compressed_data)?;
         let comp_size = compressed_data.len();
 
-        let decompressed =
-            compression::decompress_bytes(&compressed_data, compression::CompressionType::Gzip)
-                .map_err(Error::from)?;
+        let decompressed = decompress_any(&compressed_data, algorithm)?;
         let decomp_hashes = hasher.hash_single_buffer(&decompressed)?;
         let decomp_size = decompressed.len();
 
-        if !args.dry_run {
-            let do_sql = !args.json_only;
-            let do_json = !args.sql_only;
-
-            if do_sql {
-                if let Some(conn) = db_conn {
-                    insert_single_hash(config, file_path, comp_size, &comp_hashes, conn).await?;
-                    let decomp_path = file_path.with_extension("");
-                    insert_single_hash(config, &decomp_path, decomp_size, &decomp_hashes, conn)
-                        .await?;
-                }
-            }
-
-            if do_json {
-                let hash_info = output_json(file_path, comp_size, &comp_hashes, args.pretty_json);
-                let decomp_path = file_path.with_extension("");
-                output_json(&decomp_path, decomp_size, &decomp_hashes, args.pretty_json);
-                Ok(hash_info)
-            } else {
-                Ok(None)
-            }
-        } else {
-            Ok(None)
-        }
+        store_paired_hash_results(
+            config,
+            file_path,
+            comp_size,
+            &comp_hashes,
+            decomp_size,
+            &decomp_hashes,
+            args,
+            writer,
+        )
+        .await
     } else if args.decompress || args.hash_uncompressed {
         // Only hash decompressed state
-        let decompressed =
-            compression::decompress_bytes(&compressed_data, compression::CompressionType::Gzip)
-                .map_err(Error::from)?;
+        let decompressed = decompress_any(&compressed_data, algorithm)?;
         let hashes = hasher.hash_single_buffer(&decompressed)?;
         let size = decompressed.len();
 
         log_hash_results(file_path, &hashes);
-        store_hash_results(config, file_path, size, &hashes, args, db_conn).await
+        store_hash_results(config, file_path, size, &hashes, None, args, writer).await
     } else {
         // Only hash compressed state
         let hashes = hasher.hash_single_buffer(&compressed_data)?;
         let size = compressed_data.len();
 
         log_hash_results(file_path, &hashes);
-        store_hash_results(config, file_path, size, &hashes, args, db_conn).await
+        store_hash_results(config, file_path, size, &hashes, None, args, writer).await
+    }
+}
+
+// Decompresses a buffer produced by either `process_compressed_file`'s own
+// envelope-wrapped compression or a file read straight off disk in its native
+// format, without the caller having to know which case applies up front.
+fn decompress_any(bytes: &[u8], fallback_algorithm: compression::CompressionType) -> Result<Vec<u8>, Error> {
+    if compression::is_envelope(bytes) {
+        compression::decompress_envelope(bytes).map_err(Error::from)
+    } else {
+        compression::decompress_bytes(bytes, fallback_algorithm).map_err(Error::from)
     }
 }
 
@@ -169,28 +439,32 @@ async fn process_uncompressed_file(
     file_path: &Path,
     config: &Config,
     args: &HasherOptions,
-    db_conn: &mut Option<SqliteConnection>,
+    writer: &mut HashWriter<'_>,
 ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, Error> {
     let mut hasher = Hasher::new(HashConfig::from(&config.hashes));
     let (file_size, hashes) = hasher.hash_file(file_path)?;
+    let chunks = if args.chunked {
+        Some(chunk_file(file_path, args)?)
+    } else {
+        None
+    };
 
     log_hash_results(file_path, &hashes);
-    store_hash_results(config, file_path, file_size, &hashes, args, db_conn).await
+    store_hash_results(config, file_path, file_size, &hashes, chunks, args, writer).await
 }
 
 pub async fn process_single_file(
     file_path: &Path,
     config: &Config,
     args: &HasherOptions,
-    db_conn: &mut Option<SqliteConnection>,
+    writer: &mut HashWriter<'_>,
 ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, Error> {
-    let compressor =
-        compression::get_compressor(compression::CompressionType::Gzip, args.compression_level);
+    let (is_compressed, algorithm) = resolve_compression(file_path, args)?;
 
-    let result = if compressor.is_compressed_path(file_path) || args.hash_compressed {
-        process_compressed_file(file_path, config, args, db_conn).await
+    let result = if is_compressed || args.hash_compressed {
+        process_compressed_file(file_path, config, args, algorithm, is_compressed, writer).await
     } else {
-        process_uncompressed_file(file_path, config, args, db_conn).await
+        process_uncompressed_file(file_path, config, args, writer).await
     };
 
     // Handle errors according to fail_fast and silent_failures settings
@@ -231,6 +505,7 @@ pub async fn process_stdin(
             Path::new(file_path),
             buffer.len(),
             &hashes,
+            None,
             args.pretty_json,
         ))
     } else {
@@ -238,10 +513,171 @@ pub async fn process_stdin(
     }
 }
 
+// Bounded so a slow disk (or a slow writer task) applies backpressure
+// instead of letting hashed-but-uncommitted rows pile up in memory.
+const WRITER_CHANNEL_CAPACITY: usize = 64;
+
+// One file's worth of work from `hash_path_blocking`: either a plain record
+// bound for `store_hash_results`, or the two `hash_both` states bound for
+// `store_paired_hash_results` as a single merged record.
+enum HashOutcome {
+    Single {
+        path: PathBuf,
+        size: usize,
+        hashes: HashResult,
+        chunks: Option<ChunkList>,
+    },
+    Paired {
+        file_path: PathBuf,
+        compressed_size: usize,
+        compressed_hashes: HashResult,
+        decompressed_size: usize,
+        decompressed_hashes: HashResult,
+    },
+}
+
+// Synchronous counterpart to `process_compressed_file`/`process_uncompressed_file`,
+// used by `process_directory`'s worker pool so hashing can run on the blocking
+// pool instead of tying up the worker that's driving it. Forces the hasher to
+// stay single-threaded whenever file-level parallelism is in play (`--jobs` >
+// 1) so the two levels of concurrency don't oversubscribe the machine.
+fn hash_path_blocking(
+    file_path: &Path,
+    args: &HasherOptions,
+    hash_config: HashConfig,
+) -> Result<Vec<HashOutcome>, Error> {
+    let mut hasher = Hasher::new(hash_config);
+    hasher.set_sequential_only(args.jobs > 1);
+
+    let (is_compressed, algorithm) = resolve_compression(file_path, args)?;
+    let mut records = Vec::new();
+
+    if is_compressed || args.hash_compressed {
+        let compressed_data = if is_compressed {
+            std::fs::read(file_path)?
+        } else {
+            let data = std::fs::read(file_path)?;
+            compression::compress_envelope(&data, algorithm, args.compression_level).map_err(Error::from)?
+        };
+
+        if args.hash_both {
+            let comp_hashes = hasher.hash_single_buffer(&compressed_data)?;
+            let comp_size = compressed_data.len();
+
+            let decompressed = decompress_any(&compressed_data, algorithm)?;
+            let decomp_hashes = hasher.hash_single_buffer(&decompressed)?;
+            let decomp_size = decompressed.len();
+
+            records.push(HashOutcome::Paired {
+                file_path: file_path.to_path_buf(),
+                compressed_size: comp_size,
+                compressed_hashes: comp_hashes,
+                decompressed_size: decomp_size,
+                decompressed_hashes: decomp_hashes,
+            });
+        } else if args.decompress || args.hash_uncompressed {
+            let decompressed = decompress_any(&compressed_data, algorithm)?;
+            let hashes = hasher.hash_single_buffer(&decompressed)?;
+            records.push(HashOutcome::Single {
+                path: file_path.to_path_buf(),
+                size: decompressed.len(),
+                hashes,
+                chunks: None,
+            });
+        } else {
+            let hashes = hasher.hash_single_buffer(&compressed_data)?;
+            records.push(HashOutcome::Single {
+                path: file_path.to_path_buf(),
+                size: compressed_data.len(),
+                hashes,
+                chunks: None,
+            });
+        }
+    } else {
+        let (file_size, hashes) = hasher.hash_file(file_path)?;
+        let chunks = if args.chunked {
+            Some(chunk_file(file_path, args)?)
+        } else {
+            None
+        };
+        records.push(HashOutcome::Single {
+            path: file_path.to_path_buf(),
+            size: file_size,
+            hashes,
+            chunks,
+        });
+    }
+
+    Ok(records)
+}
+
+// Runs `hash_path_blocking` on the blocking pool, then funnels each resulting
+// record through `writer`/`output_json` exactly like the sequential
+// `process_single_file` path does. Errors are handled the same way too
+// (fail-fast vs. logged-and-skipped), so switching `--jobs` doesn't change
+// behavior, only throughput.
+async fn process_directory_entry(
+    file_path: PathBuf,
+    config: &Config,
+    args: &HasherOptions,
+    hash_config: HashConfig,
+    writer: &mut HashWriter<'_>,
+) -> Result<Option<serde_json::Map<String, serde_json::Value>>, Error> {
+    let args_owned = args.clone();
+    let path_for_blocking = file_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        hash_path_blocking(&path_for_blocking, &args_owned, hash_config)
+    })
+    .await
+    .map_err(|e| Error::Join(e.to_string()))?;
+
+    let records = match result {
+        Ok(records) => records,
+        Err(e) if !args.fail_fast => {
+            if !args.silent_failures {
+                error!("Failed to hash {}: {}", file_path.display(), e);
+            }
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut last_hash_info = None;
+    for outcome in records {
+        last_hash_info = match outcome {
+            HashOutcome::Single { path, size, hashes, chunks } => {
+                store_hash_results(config, &path, size, &hashes, chunks, args, writer).await?
+            }
+            HashOutcome::Paired {
+                file_path,
+                compressed_size,
+                compressed_hashes,
+                decompressed_size,
+                decompressed_hashes,
+            } => {
+                store_paired_hash_results(
+                    config,
+                    &file_path,
+                    compressed_size,
+                    &compressed_hashes,
+                    decompressed_size,
+                    &decompressed_hashes,
+                    args,
+                    writer,
+                )
+                .await?
+            }
+        };
+    }
+
+    Ok(last_hash_info)
+}
+
 pub async fn process_directory(
     path_to_hash: &Path,
     args: &HasherOptions,
     config: &Config,
+    resume: bool,
 ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, Error> {
     let mut db_conn = if !args.json_only {
         Some(SqliteConnection::connect(&config.database.db_string).await?)
@@ -249,23 +685,148 @@ pub async fn process_directory(
         None
     };
 
-    let mut file_count = 0;
-    let mut last_hash_info = None;
-    let walker = WalkDir::new(path_to_hash)
+    let jobs = args.jobs.max(1);
+    let hash_config = HashConfig::from(&config.hashes);
+
+    let mut paths = Vec::new();
+    for entry in WalkDir::new(path_to_hash)
         .min_depth(0)
         .max_depth(args.max_depth)
         .follow_links(!args.no_follow_symlinks)
         .contents_first(!args.breadth_first)
-        .sort_by_file_name();
-
-    for entry in walker {
+        .sort_by_file_name()
+    {
         if let Ok(entry) = entry {
             if !entry.path().is_dir() {
-                file_count += 1;
-                last_hash_info =
-                    process_single_file(entry.path(), config, args, &mut db_conn).await?;
+                paths.push(entry.path().to_path_buf());
+            }
+        }
+    }
+
+    // Prefilter against already-hashed paths before `db_conn` is handed off
+    // to the writer task below, so this is the only place that needs the
+    // connection for reads.
+    if resume {
+        if let Some(conn) = db_conn.as_mut() {
+            let filter = ResumeFilter::load(config, conn).await?;
+            let mut remaining = Vec::with_capacity(paths.len());
+            let mut skipped = 0usize;
+
+            for path in paths {
+                if filter.should_skip(config, &path, conn).await? {
+                    skipped += 1;
+                } else {
+                    remaining.push(path);
+                }
+            }
+
+            info!(
+                "Resume: skipped {} already-hashed file(s), {} remaining to hash",
+                skipped,
+                remaining.len()
+            );
+            paths = remaining;
+        }
+    }
+    let file_count = paths.len();
+
+    // Scales with `jobs` (matching `copy_directory`'s channel) so raising
+    // `--jobs` doesn't shrink the effective buffer per worker and immediately
+    // reintroduce the backpressure this pool is meant to avoid.
+    let (record_tx, mut record_rx) = mpsc::channel::<HashRecord>((jobs * 4).max(WRITER_CHANNEL_CAPACITY));
+
+    // The only place that touches `db_conn`; workers below hand off finished
+    // hashes over the channel instead of awaiting each insert inline, so
+    // filesystem reads and hashing can overlap DB commits.
+    let db_writer = async {
+        let mut batch = HashBatch::default();
+        let mut db_error = None;
+
+        while let Some(record) = record_rx.recv().await {
+            if let Some(conn) = db_conn.as_mut() {
+                if let Some(chunks) = &record.chunks {
+                    if let Err(e) = insert_chunked_hash(config, &record.path, chunks, conn).await {
+                        db_error.get_or_insert(e);
+                    }
+                }
+
+                if let Some(paired) = &record.paired {
+                    if let Err(e) = insert_paired_hash(
+                        config,
+                        &record.path,
+                        &paired.content_name,
+                        paired.compressed_size,
+                        paired.decompressed_size,
+                        conn,
+                    )
+                    .await
+                    {
+                        db_error.get_or_insert(e);
+                    }
+                }
+
+                batch.push(record.path, record.size, record.hashes);
+                if batch.should_flush() {
+                    if let Err(e) = batch.flush(config, conn).await {
+                        db_error.get_or_insert(e);
+                    }
+                }
             }
         }
+
+        if let Some(conn) = db_conn.as_mut() {
+            if let Err(e) = batch.flush(config, conn).await {
+                db_error.get_or_insert(e);
+            }
+        }
+
+        db_error
+    };
+
+    // Shared across workers purely so the function can keep returning "the
+    // last hash computed", matching the sequential path's return value; with
+    // `--jobs` > 1 that's whichever worker happens to finish last, not
+    // necessarily the walk's last entry.
+    let last_hash_info = Arc::new(Mutex::new(None));
+    let worker_error: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
+    // Kept outside the `move` closure below so they're still readable once
+    // the workers have all finished.
+    let last_hash_info_result = last_hash_info.clone();
+    let worker_error_result = worker_error.clone();
+
+    // `move` so this closure (not just each per-file task) owns `record_tx`;
+    // it's dropped once the stream is fully drained, closing the channel so
+    // `db_writer` can finish.
+    let workers = stream::iter(paths.into_iter().map(move |path| {
+        let record_tx = record_tx.clone();
+        let hash_config = hash_config.clone();
+        let last_hash_info = last_hash_info.clone();
+        let worker_error = worker_error.clone();
+        async move {
+            if worker_error.lock().unwrap().is_some() {
+                return;
+            }
+
+            let mut writer = HashWriter::Channel(&record_tx);
+            match process_directory_entry(path, config, args, hash_config, &mut writer).await {
+                Ok(Some(info)) => *last_hash_info.lock().unwrap() = Some(info),
+                Ok(None) => {}
+                Err(e) => {
+                    worker_error.lock().unwrap().get_or_insert(e);
+                }
+            }
+        }
+    }))
+    .buffer_unordered(jobs)
+    .collect::<Vec<()>>();
+
+    let (_, db_error) = futures::join!(workers, db_writer);
+
+    if let Some(e) = worker_error_result.lock().unwrap().take() {
+        return Err(e);
+    }
+    if let Some(e) = db_error {
+        return Err(e);
     }
 
     info!(
@@ -274,5 +835,6 @@ pub async fn process_directory(
         path_to_hash.display()
     );
 
+    let last_hash_info = last_hash_info_result.lock().unwrap().clone();
     Ok(last_hash_info)
 }
@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use bloomfilter::Bloom;
+use log::info;
+use sqlx::SqliteConnection;
+
+use crate::configuration::Config;
+use crate::database::{count_rows, path_exists, scan_file_paths};
+use crate::utils::Error;
+
+// Chosen low enough that the extra confirmation query (see `should_skip`)
+// stays rare, without growing the filter unreasonably large.
+const FALSE_POSITIVE_RATE: f64 = 1e-6;
+
+// Backs `--resume` with a Bloom filter over `file_path` instead of holding
+// every already-hashed path in memory for the whole walk. Seeding still
+// reads every path at once (`scan_file_paths` returns them as one `Vec`,
+// dropped once the filter is built), so memory at startup grows with the
+// row count.
+pub struct ResumeFilter {
+    filter: Option<Bloom<String>>,
+}
+
+impl ResumeFilter {
+    // Seeds the filter from a single `SELECT file_path` scan of the
+    // configured table. An empty table has nothing to resume from, so
+    // `should_skip` is wired to always report false without touching the
+    // database again.
+    pub async fn load(config: &Config, db_conn: &mut SqliteConnection) -> Result<Self, Error> {
+        let row_count = count_rows(config, db_conn).await?;
+        if row_count <= 0 {
+            return Ok(Self { filter: None });
+        }
+
+        let mut filter = Bloom::new_for_fp_rate(row_count as usize, FALSE_POSITIVE_RATE);
+        for path in scan_file_paths(config, db_conn).await? {
+            filter.set(&path);
+        }
+
+        info!(
+            "Resume: loaded filter from {} existing row(s) (target false-positive rate {:e})",
+            row_count, FALSE_POSITIVE_RATE
+        );
+
+        Ok(Self {
+            filter: Some(filter),
+        })
+    }
+
+    // A "maybe present" answer is only ever a hint: the filter's false
+    // positives mean it must be confirmed with an indexed lookup before
+    // skipping. A "definitely absent" answer is authoritative, so brand-new
+    // files skip that lookup entirely.
+    pub async fn should_skip(
+        &self,
+        config: &Config,
+        file_path: &Path,
+        db_conn: &mut SqliteConnection,
+    ) -> Result<bool, Error> {
+        let Some(filter) = &self.filter else {
+            return Ok(false);
+        };
+
+        if !filter.check(&file_path.display().to_string()) {
+            return Ok(false);
+        }
+
+        path_exists(config, file_path, db_conn).await
+    }
+}
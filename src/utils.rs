@@ -17,6 +17,24 @@ pub enum Error {
     Join(String),
 }
 
+impl Error {
+    /// Stable, machine-readable name for the variant, for use in structured
+    /// output (JSON error records) where the `Display` message is too free-form.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::IO(_) => "io",
+            Error::ThreadPanic => "thread_panic",
+            Error::Database(_) => "database",
+            Error::FileChanged => "file_changed",
+            Error::DiskSpace => "disk_space",
+            Error::DbLocked => "db_locked",
+            Error::Config(_) => "config",
+            Error::Download(_) => "download",
+            Error::Join(_) => "join",
+        }
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
         match e.kind() {